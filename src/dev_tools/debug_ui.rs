@@ -2,7 +2,10 @@
 
 use std::any::Any as _;
 
-use super::input::{ForceFreeCursor, ToggleDebugUi};
+use super::{
+    input::{ForceFreeCursor, ToggleDebugUi},
+    npc_ai::NpcAiGizmos,
+};
 use crate::RenderLayer;
 use crate::font::FontAssets;
 use crate::gameplay::crosshair::CrosshairState;
@@ -92,6 +95,7 @@ pub(super) fn plugin(app: &mut App) {
             toggle_lighting_debug_ui.run_if(toggled_state(DebugState::Lighting)),
             toggle_physics_debug_ui.run_if(toggled_state(DebugState::Physics)),
             toggle_landmass_debug_ui.run_if(toggled_state(DebugState::Landmass)),
+            toggle_npc_ai_debug_ui.run_if(toggled_state(DebugState::NpcAi)),
         )
             .chain()
             .in_set(PostPhysicsAppSystems::ChangeUi),
@@ -139,6 +143,7 @@ fn update_debug_ui_text(
         DebugState::Lighting => "Lighting",
         DebugState::Physics => "Physics",
         DebugState::Landmass => "Landmass",
+        DebugState::NpcAi => "NPC AI",
     }
     .to_string();
 }
@@ -169,6 +174,12 @@ fn toggle_landmass_debug_ui(mut debug: ResMut<EnableLandmassDebug>) {
     **debug = !**debug;
 }
 
+#[cfg_attr(feature = "hot_patch", hot)]
+fn toggle_npc_ai_debug_ui(mut config_store: ResMut<GizmoConfigStore>) {
+    let config = config_store.config_mut::<NpcAiGizmos>().0;
+    config.enabled = !config.enabled;
+}
+
 #[cfg_attr(feature = "hot_patch", hot)]
 fn toggle_fps_overlay(mut config: ResMut<FpsOverlayConfig>) {
     config.enabled = !config.enabled;
@@ -207,6 +218,7 @@ enum DebugState {
     Lighting,
     Physics,
     Landmass,
+    NpcAi,
 }
 
 impl DebugState {
@@ -216,7 +228,8 @@ impl DebugState {
             Self::Ui => Self::Lighting,
             Self::Lighting => Self::Physics,
             Self::Physics => Self::Landmass,
-            Self::Landmass => Self::None,
+            Self::Landmass => Self::NpcAi,
+            Self::NpcAi => Self::None,
         }
     }
 }
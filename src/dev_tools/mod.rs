@@ -6,6 +6,7 @@ use bevy_simple_subsecond_system::prelude::*;
 
 mod debug_ui;
 mod input;
+mod npc_ai;
 mod validate_preloading;
 
 use crate::{menus::Menu, screens::loading::LoadingScreen};
@@ -28,6 +29,7 @@ pub(super) fn plugin(app: &mut App) {
         ),
         debug_ui::plugin,
         input::plugin,
+        npc_ai::plugin,
         validate_preloading::plugin,
     ));
 }
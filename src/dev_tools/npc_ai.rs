@@ -0,0 +1,61 @@
+//! Gizmos showing what each NPC decided to do and where it is headed.
+
+use bevy::{color::palettes::tailwind, prelude::*, render::view::RenderLayers};
+use bevy_landmass::prelude::*;
+
+use crate::{
+    RenderLayer,
+    gameplay::npc::{
+        behavior::{Action, Decision},
+        navigation::Agent,
+        stats::NpcStats,
+    },
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_gizmo_config(
+        NpcAiGizmos,
+        GizmoConfig {
+            enabled: false,
+            render_layers: RenderLayers::from(RenderLayer::GIZMO3),
+            ..default()
+        },
+    );
+    app.add_systems(Update, draw_decisions);
+}
+
+#[derive(Default, Reflect, GizmoConfigGroup)]
+pub(super) struct NpcAiGizmos;
+
+fn action_color(action: Action) -> Srgba {
+    match action {
+        Action::Idle => tailwind::GRAY_400,
+        Action::Wander => tailwind::SKY_400,
+        Action::Investigate => tailwind::YELLOW_400,
+        Action::Alert => tailwind::ORANGE_400,
+        Action::Chase => tailwind::RED_500,
+        Action::Melee => tailwind::FUCHSIA_500,
+        Action::Spit => tailwind::LIME_400,
+        Action::Reposition => tailwind::CYAN_400,
+    }
+}
+
+fn draw_decisions(
+    mut gizmos: Gizmos<NpcAiGizmos>,
+    npcs: Query<(&Transform, &Decision, &NpcStats, &Agent)>,
+    targets: Query<&AgentTarget3d>,
+) {
+    for (transform, decision, stats, agent) in &npcs {
+        let color = action_color(decision.action);
+        let head = transform.translation + Vec3::Y * (stats.half_height() + 0.5);
+        // The more convinced the NPC is of its decision, the bigger the marker.
+        gizmos.sphere(
+            Isometry3d::from_translation(head),
+            0.1 + 0.3 * decision.score.clamp(0.0, 1.0),
+            color,
+        );
+        if let Ok(AgentTarget3d::Point(target)) = targets.get(**agent) {
+            gizmos.line(transform.translation, *target, color.with_alpha(0.5));
+        }
+    }
+}
@@ -0,0 +1,176 @@
+//! Tracking of explosion chain reactions and the combos they score.
+//!
+//! Every explosion belongs to a chain. An explosion that was not set off by another one starts
+//! a new chain, and everything its blast damages carries the chain's [`ChainLink`] one level deeper.
+//! When nothing in a chain has exploded or died for [`CHAIN_TIMEOUT`], the chain is finished
+//! and [`ChainFinished`] is triggered with its statistics.
+
+#[cfg(test)]
+mod tests;
+
+use std::time::Duration;
+
+use bevy::{platform::collections::HashMap, prelude::*};
+
+use crate::{
+    gameplay::{health::OnDeath, npc::Npc},
+    screens::Screen,
+};
+
+/// How long a chain stays active without any new explosions or kills.
+/// Must be longer than the delay between an explosion and the damage it deals.
+pub(crate) const CHAIN_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// The bonus score awarded for every kill of a chain, before the combo multiplier.
+const KILL_BONUS: f32 = 100.0;
+
+pub(crate) fn plugin(app: &mut App) {
+    app.register_type::<(ChainLink, ChainStats)>();
+    app.init_resource::<Chains>();
+
+    app.add_systems(OnEnter(Screen::Gameplay), reset_chains);
+    app.add_systems(Update, finish_chains.run_if(in_state(Screen::Gameplay)));
+    app.add_observer(count_chain_kills);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Reflect)]
+pub(crate) struct ChainId(u32);
+
+/// The chain reaction an entity was last hit by, or an explosion belongs to.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub(crate) struct ChainLink {
+    pub(crate) id: ChainId,
+    /// How many explosions came before this one in the chain. The first explosion has depth 0.
+    pub(crate) depth: u32,
+}
+
+impl ChainLink {
+    /// The link of anything hit by an explosion with this link.
+    pub(crate) fn next(self) -> Self {
+        Self {
+            depth: self.depth + 1,
+            ..self
+        }
+    }
+}
+
+/// Statistics of a single chain reaction.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Reflect)]
+pub(crate) struct ChainStats {
+    /// The number of explosions in the chain.
+    pub(crate) explosions: u32,
+    /// The number of enemies killed by the chain.
+    pub(crate) kills: u32,
+    /// The deepest link of the chain.
+    pub(crate) depth: u32,
+    /// The time since the last explosion or kill.
+    idle: Duration,
+}
+
+impl ChainStats {
+    /// The score multiplier of the chain, growing with every explosion after the first.
+    pub(crate) fn multiplier(&self) -> f32 {
+        1.0 + 0.5 * self.explosions.saturating_sub(1) as f32
+    }
+
+    /// The bonus score of the chain, on top of the score of the kills themselves.
+    /// Chains of a single explosion have no bonus.
+    pub(crate) fn score(&self) -> u32 {
+        (self.kills as f32 * KILL_BONUS * (self.multiplier() - 1.0)).round() as u32
+    }
+
+    /// Whether `self` is a longer chain than `other`.
+    fn is_longer_than(&self, other: &Self) -> bool {
+        (self.explosions, self.kills) > (other.explosions, other.kills)
+    }
+}
+
+/// An event that is triggered when a chain reaction is over.
+#[derive(Event, Clone, Copy, Debug)]
+pub(crate) struct ChainFinished {
+    pub(crate) stats: ChainStats,
+}
+
+/// The chain reactions of the current run.
+#[derive(Resource, Debug, Default)]
+pub(crate) struct Chains {
+    next_id: u32,
+    active: HashMap<ChainId, ChainStats>,
+    longest: ChainStats,
+}
+
+impl Chains {
+    /// Returns the link of an explosion, continuing the given chain if it is still active
+    /// and starting a new one otherwise, and records the explosion.
+    pub(crate) fn record_explosion(&mut self, link: Option<ChainLink>) -> ChainLink {
+        let link = match link {
+            Some(link) if self.active.contains_key(&link.id) => link,
+            _ => self.start(),
+        };
+        let stats = self.active.entry(link.id).or_default();
+        stats.explosions += 1;
+        stats.depth = stats.depth.max(link.depth);
+        stats.idle = Duration::ZERO;
+        link
+    }
+
+    fn start(&mut self) -> ChainLink {
+        let id = ChainId(self.next_id);
+        self.next_id += 1;
+        ChainLink { id, depth: 0 }
+    }
+
+    fn record_kill(&mut self, link: ChainLink) {
+        if let Some(stats) = self.active.get_mut(&link.id) {
+            stats.kills += 1;
+            stats.idle = Duration::ZERO;
+        }
+    }
+
+    /// The active chain with the most explosions.
+    pub(crate) fn current(&self) -> Option<&ChainStats> {
+        self.active
+            .values()
+            .max_by_key(|stats| (stats.explosions, stats.kills))
+    }
+
+    /// The longest chain of the run, including the ones that are still active.
+    pub(crate) fn longest(&self) -> ChainStats {
+        self.current()
+            .filter(|current| current.is_longer_than(&self.longest))
+            .copied()
+            .unwrap_or(self.longest)
+    }
+}
+
+fn reset_chains(mut commands: Commands) {
+    commands.insert_resource(Chains::default());
+}
+
+fn count_chain_kills(
+    trigger: Trigger<OnDeath>,
+    enemies: Query<&ChainLink, With<Npc>>,
+    mut chains: ResMut<Chains>,
+) {
+    if let Ok(link) = enemies.get(trigger.target()) {
+        chains.record_kill(*link);
+    }
+}
+
+fn finish_chains(mut chains: ResMut<Chains>, time: Res<Time>, mut commands: Commands) {
+    let Chains {
+        active, longest, ..
+    } = &mut *chains;
+    active.retain(|_, stats| {
+        stats.idle += time.delta();
+        if stats.idle < CHAIN_TIMEOUT {
+            return true;
+        }
+        if stats.is_longer_than(longest) {
+            *longest = *stats;
+        }
+        commands.trigger(ChainFinished { stats: *stats });
+        false
+    });
+}
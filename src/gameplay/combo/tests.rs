@@ -0,0 +1,36 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use super::{ChainFinished, ChainStats, Chains};
+use crate::{
+    gameplay::explosion::OnExplode,
+    headless::{headless_app, run_for, run_updates, spawn_barrel_row},
+};
+
+#[derive(Resource, Default)]
+struct FinishedChains(Vec<ChainStats>);
+
+#[test]
+fn barrel_chain_is_tracked_as_one_chain() {
+    let mut app = headless_app();
+    app.init_resource::<FinishedChains>();
+    app.add_observer(
+        |trigger: Trigger<ChainFinished>, mut finished: ResMut<FinishedChains>| {
+            finished.0.push(trigger.stats);
+        },
+    );
+
+    let barrels = spawn_barrel_row(&mut app, 4, 2.5);
+    run_updates(&mut app, 2);
+
+    app.world_mut()
+        .trigger_targets(OnExplode::default(), barrels[0]);
+    run_for(&mut app, Duration::from_secs(3));
+
+    let finished = &app.world().resource::<FinishedChains>().0;
+    assert_eq!(finished.len(), 1, "{finished:?}");
+    assert_eq!(finished[0].explosions, 4);
+    assert_eq!(finished[0].depth, 3);
+    assert_eq!(app.world().resource::<Chains>().longest(), finished[0]);
+}
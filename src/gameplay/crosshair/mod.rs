@@ -2,7 +2,7 @@
 //! The crosshair is a UI element that is used to indicate the player's aim. We change the crosshair when the player is looking at a prop or an NPC.
 //! This is done by registering which systems are interested in the crosshair state.
 
-use crate::{PostPhysicsAppSystems, screens::Screen};
+use crate::{PostPhysicsAppSystems, gameplay::player::gunplay::OnHeadshot, screens::Screen};
 use assets::CROSSHAIR_DOT_PATH;
 use bevy::{platform::collections::HashSet, prelude::*, window::CursorGrabMode};
 #[cfg(feature = "hot_patch")]
@@ -12,7 +12,7 @@ use std::any::{Any as _, TypeId};
 pub(crate) mod assets;
 
 pub(super) fn plugin(app: &mut App) {
-    app.register_type::<CrosshairState>();
+    app.register_type::<(CrosshairState, HeadshotFlash)>();
 
     app.add_systems(
         Update,
@@ -21,6 +21,8 @@ pub(super) fn plugin(app: &mut App) {
             .in_set(PostPhysicsAppSystems::ChangeUi),
     );
     app.add_systems(OnEnter(Screen::Gameplay), spawn_crosshair);
+    app.add_observer(flash_on_headshot);
+    app.add_systems(Update, fade_headshot_flash);
 
     app.add_plugins(assets::plugin);
 }
@@ -96,3 +98,37 @@ fn update_crosshair(
         *visibility = Visibility::Hidden;
     }
 }
+
+/// Tints the crosshair after a headshot, fading back to white as the timer finishes.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+struct HeadshotFlash(Timer);
+
+const HEADSHOT_FLASH_COLOR: Color = Color::srgb(1.0, 0.15, 0.1);
+
+fn flash_on_headshot(
+    _trigger: Trigger<OnHeadshot>,
+    crosshair: Option<Single<Entity, With<CrosshairState>>>,
+    mut commands: Commands,
+) {
+    let Some(crosshair) = crosshair else {
+        return;
+    };
+    commands
+        .entity(*crosshair)
+        .insert(HeadshotFlash(Timer::from_seconds(0.3, TimerMode::Once)));
+}
+
+fn fade_headshot_flash(
+    mut crosshair: Query<(Entity, &mut HeadshotFlash, &mut ImageNode)>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut flash, mut image_node) in &mut crosshair {
+        flash.0.tick(time.delta());
+        image_node.color = HEADSHOT_FLASH_COLOR.mix(&Color::WHITE, flash.0.fraction());
+        if flash.0.finished() {
+            commands.entity(entity).remove::<HeadshotFlash>();
+        }
+    }
+}
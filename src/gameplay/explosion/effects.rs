@@ -25,7 +25,7 @@ use crate::{
         gore_settings::{Gore, GoreSettings},
         health::OnDeath,
         npc::stats::NpcStats,
-        player::{Player, gunplay::WeaponStats},
+        player::{Player, weapons::WeaponInventory},
     },
     screens::Screen,
 };
@@ -91,7 +91,7 @@ fn on_explode_prop(
 fn on_enemy_death(
     trigger: Trigger<OnDeath>,
     query: Query<(&GlobalTransform, Option<&NpcStats>), With<ExplodeOnDeath>>,
-    weapon_stats: Single<&WeaponStats, With<Player>>,
+    inventory: Single<&WeaponInventory, With<Player>>,
     mut explosion_assets: ResMut<ExplosionAssets>,
     gore_settings: Res<GoreSettings>,
     mut commands: Commands,
@@ -106,7 +106,7 @@ fn on_enemy_death(
     let properties = EffectProperties::default().with_properties([(
         "scale".to_string(),
         Value::Scalar(ScalarValue::Float(
-            scale + weapon_stats.extra_enemy_explosion_radius / scale,
+            scale + inventory.stats().extra_enemy_explosion_radius / scale,
         )),
     )]);
     commands.spawn((
@@ -135,7 +135,7 @@ fn on_enemy_death(
     let scale_x = rng.gen_range(0.8..1.2);
     let scale_y = rng.gen_range(0.8..1.2);
     let scale_z = rng.gen_range(0.8..1.2);
-    let base_scale = scale * (2.5 + weapon_stats.extra_enemy_explosion_radius);
+    let base_scale = scale * (2.5 + inventory.stats().extra_enemy_explosion_radius);
 
     let spray_transform = transform
         .with_translation(transform.translation - Vec3::Y * 0.3)
@@ -1,6 +1,11 @@
 pub(crate) mod assets;
 pub(crate) mod effects;
+#[cfg(test)]
+mod tests;
 
+use std::str::FromStr;
+
+use anyhow::Context as _;
 use avian3d::prelude::*;
 use bevy::{ecs::system::SystemParam, prelude::*};
 #[cfg(feature = "hot_patch")]
@@ -10,8 +15,9 @@ use crate::{
     auto_timer::{AutoTimer, OnAutoTimerFinish},
     despawn_after::Despawn,
     gameplay::{
-        health::{Health, OnDamage, OnDeath},
-        player::{Player, gunplay::WeaponStats},
+        combo::{ChainLink, Chains},
+        health::{DamageKind, Health, OnDamage, OnDeath},
+        player::{Player, weapons::WeaponInventory},
     },
     third_party::avian3d::CollisionLayer,
 };
@@ -19,9 +25,13 @@ use crate::{
 pub const EXPLOSION_PLAYER_DAMAGE_SCALE: f32 = 0.1;
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_plugins((assets::plugin, effects::plugin));
+    app.add_plugins((assets::plugin, effects::plugin, simulation_plugin));
+}
 
+/// The explosion logic, without any visual or audio effects.
+pub(crate) fn simulation_plugin(app: &mut App) {
     app.register_type::<(Explosive, ExplodeOnShoot, ExplodeOnContact)>();
+    app.register_type::<(ExplosionFalloff, BlastShield)>();
 
     app.add_observer(on_shoot_explosive);
     app.add_observer(on_touch_explosive);
@@ -50,12 +60,16 @@ pub(super) fn plugin(app: &mut App) {
 /// A component for making an entity an explosive.
 ///
 /// The explosion can be activated by triggering the [`OnExplode`] event.
-#[derive(Component, Clone, Copy, Debug, PartialEq, Reflect)]
+#[derive(Component, Clone, Debug, Reflect)]
 #[reflect(Component)]
 pub(crate) struct Explosive {
-    // TODO: Do we want to support a falloff?
-    /// The radius of the explosion. Only entities within this radius will be affected.
+    /// The outer radius of the explosion. Only entities within this radius will be affected.
     pub(crate) radius: f32,
+    /// Entities within this radius receive the full damage and impulse.
+    /// Between the inner and outer radius, both are scaled down according to the [`falloff`](Self::falloff).
+    pub(crate) inner_radius: f32,
+    /// How the damage and impulse decrease between the inner and outer radius.
+    pub(crate) falloff: ExplosionFalloff,
     /// The strength of the explosion impulse.
     pub(crate) impulse_strength: f32,
     /// The damage dealt by the explosion.
@@ -68,6 +82,8 @@ impl Default for Explosive {
     fn default() -> Self {
         Self {
             radius: 3.5,
+            inner_radius: 1.0,
+            falloff: ExplosionFalloff::default(),
             impulse_strength: 25.0,
             damage: 100.0,
             damages_player: true,
@@ -75,9 +91,101 @@ impl Default for Explosive {
     }
 }
 
+impl Explosive {
+    /// Returns how much of the damage and impulse is applied at the given distance
+    /// from the center of the explosion, in the range `[0, 1]`.
+    pub(crate) fn falloff_at(&self, distance: f32) -> f32 {
+        if distance <= self.inner_radius {
+            return 1.0;
+        }
+        if distance >= self.radius {
+            return 0.0;
+        }
+        let t = (distance - self.inner_radius) / (self.radius - self.inner_radius);
+        let factor = match &self.falloff {
+            ExplosionFalloff::None => 1.0,
+            ExplosionFalloff::Linear => 1.0 - t,
+            ExplosionFalloff::InverseSquare => {
+                // Shift and rescale the inverse-square curve so that it is 1 at the inner radius
+                // and 0 at the outer radius instead of never quite reaching zero.
+                let inner = self.inner_radius.max(MIN_INVERSE_SQUARE_RADIUS);
+                let at_outer = (inner / self.radius).powi(2);
+                ((inner / distance).powi(2) - at_outer) / (1.0 - at_outer)
+            }
+            ExplosionFalloff::Custom(curve) => curve.sample_clamped(t),
+        };
+        factor.clamp(0.0, 1.0)
+    }
+}
+
+/// The distance below which inverse-square falloff is not applied, to avoid dividing by zero.
+const MIN_INVERSE_SQUARE_RADIUS: f32 = 0.1;
+
+/// How the damage and impulse of an [`Explosive`] decrease between its inner and outer radius.
+#[derive(Clone, Debug, Default, Reflect)]
+pub(crate) enum ExplosionFalloff {
+    /// Full damage and impulse up to the outer radius.
+    None,
+    /// Decreases linearly from full strength at the inner radius to zero at the outer radius.
+    #[default]
+    Linear,
+    /// Decreases with the inverse square of the distance, reaching zero at the outer radius.
+    InverseSquare,
+    /// A curve sampled over `[0, 1]`, where 0 is the inner radius and 1 is the outer radius.
+    Custom(SampleAutoCurve<f32>),
+}
+
+impl FromStr for ExplosionFalloff {
+    type Err = anyhow::Error;
+
+    /// Parses `none`, `linear`, `inverse_square`, or a comma separated list of evenly spaced
+    /// samples for a custom curve, such as `1, 0.9, 0.5, 0`.
+    fn from_str(text: &str) -> anyhow::Result<Self> {
+        match text.trim() {
+            "none" => Ok(Self::None),
+            "linear" => Ok(Self::Linear),
+            "inverse_square" => Ok(Self::InverseSquare),
+            samples => {
+                let samples = samples
+                    .split(',')
+                    .map(|sample| {
+                        let sample = sample.trim();
+                        sample
+                            .parse::<f32>()
+                            .with_context(|| format!("\"{sample}\" is not a number"))
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                let curve = SampleAutoCurve::new(Interval::UNIT, samples)
+                    .context("A custom falloff needs at least two samples")?;
+                Ok(Self::Custom(curve))
+            }
+        }
+    }
+}
+
+/// A component for props that absorb explosions, protecting whatever is behind them.
+///
+/// Level geometry always blocks explosions completely, so this is only needed for props
+/// that are not part of the [`CollisionLayer::Default`] layer, such as dynamic props.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Component)]
+pub(crate) struct BlastShield {
+    /// The fraction of damage and impulse absorbed by this prop, from 0 (none) to 1 (all).
+    pub(crate) absorption: f32,
+}
+
+impl Default for BlastShield {
+    fn default() -> Self {
+        Self { absorption: 0.75 }
+    }
+}
+
 /// An event that is triggered when an explosive should explode.
-#[derive(Event, Clone, Copy, Debug, PartialEq)]
-pub(crate) struct OnExplode;
+#[derive(Event, Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct OnExplode {
+    /// The chain reaction that set off this explosion. `None` starts a new chain.
+    pub(crate) chain: Option<ChainLink>,
+}
 
 /// A marker component for entities that have exploded or are in the process of exploding.
 #[derive(Component, Clone, Copy, Debug, Default, PartialEq, Reflect)]
@@ -126,13 +234,15 @@ pub(crate) struct ExplodeOnDeath;
 #[cfg_attr(feature = "hot_patch", hot)]
 fn on_shoot_explosive(
     trigger: Trigger<OnDeath>,
-    explosive_query: Query<&ExplodeOnShoot>,
+    explosive_query: Query<Option<&ChainLink>, With<ExplodeOnShoot>>,
     mut commands: Commands,
 ) {
     let entity = trigger.target();
-    if let Ok(_explosive) = explosive_query.get(entity) {
-        // Trigger the explosion.
-        commands.entity(entity).trigger(OnExplode);
+    if let Ok(chain) = explosive_query.get(entity) {
+        // Trigger the explosion, continuing the chain that destroyed the explosive.
+        commands.entity(entity).trigger(OnExplode {
+            chain: chain.copied(),
+        });
     }
 }
 
@@ -163,26 +273,33 @@ fn on_touch_explosive(
     }
 
     // Trigger the explosion.
-    commands.entity(body).trigger(OnExplode);
+    commands.entity(body).trigger(OnExplode::default());
 }
 
 #[cfg_attr(feature = "hot_patch", hot)]
 fn on_enemy_death(
     trigger: Trigger<OnDeath>,
     mut commands: Commands,
-    explosive_query: Query<(&GlobalTransform, &Explosive), With<ExplodeOnDeath>>,
-    weapon_stats: Single<&WeaponStats, With<Player>>,
+    explosive_query: Query<
+        (&GlobalTransform, &Explosive, Option<&ChainLink>),
+        With<ExplodeOnDeath>,
+    >,
+    inventory: Option<Single<&WeaponInventory, With<Player>>>,
 ) {
     let entity = trigger.target();
 
     // Get the explosive properties and transform of the entity.
-    if let Ok((transform, explosive)) = explosive_query.get(entity) {
+    if let Ok((transform, explosive, chain)) = explosive_query.get(entity) {
         // Trigger the explosion. We use a separate entity with a timer
         // to delay the explosion until the dismembered body parts of enemies
         // are ready for physics.
         // Hacky, but this is a game jam :D could be cleaned up though
-        let mut explosive = *explosive;
-        explosive.radius += weapon_stats.extra_enemy_explosion_radius;
+        let mut explosive = explosive.clone();
+        if let Some(inventory) = inventory {
+            explosive.radius += inventory.stats().extra_enemy_explosion_radius;
+        }
+        // Continue the chain reaction that killed the enemy.
+        let chain = chain.copied();
         commands
             .spawn((
                 RigidBody::Static,
@@ -192,10 +309,10 @@ fn on_enemy_death(
                 explosive,
             ))
             .observe(
-                |trigger: Trigger<OnAutoTimerFinish>, mut commands: Commands| {
+                move |trigger: Trigger<OnAutoTimerFinish>, mut commands: Commands| {
                     commands
                         .entity(trigger.target())
-                        .trigger(OnExplode)
+                        .trigger(OnExplode { chain })
                         .despawn();
                 },
             );
@@ -206,6 +323,7 @@ fn on_enemy_death(
 fn on_explode(
     trigger: Trigger<OnExplode>,
     query: Query<(&Explosive, &GlobalTransform, &ComputedCenterOfMass), Without<Exploded>>,
+    mut chains: ResMut<Chains>,
     mut explosion_helper: ExplosionHelper,
 ) {
     let entity = trigger.target();
@@ -232,8 +350,11 @@ fn on_explode(
         .entity(entity)
         .try_insert(Exploded);
 
+    // Continue the chain reaction that set off the explosion, or start a new one.
+    let chain = chains.record_explosion(trigger.chain);
+
     // Apply the explosion at the center of mass of the explosive.
-    explosion_helper.apply_explosion(explosive, explosive_global_com);
+    explosion_helper.apply_explosion(entity, explosive, explosive_global_com, chain);
 
     // Despawn the explosive entity after the explosion.
     explosion_helper.commands.entity(entity).insert(Despawn);
@@ -257,15 +378,85 @@ pub(crate) struct ExplosionHelper<'w, 's> {
         ),
     >,
     damageable_query: Query<'w, 's, Has<Player>, With<Health>>,
+    occlusion: ExplosionOcclusion<'w, 's>,
     spatial_query: SpatialQuery<'w, 's>,
     commands: Commands<'w, 's>,
 }
 
+/// A [`SystemParam`] for determining how much of an explosion reaches a body.
+#[derive(SystemParam)]
+struct ExplosionOcclusion<'w, 's> {
+    collider_of_query: Query<'w, 's, &'static ColliderOf>,
+    layers_query: Query<'w, 's, &'static CollisionLayers>,
+    shield_query: Query<'w, 's, &'static BlastShield>,
+}
+
+impl ExplosionOcclusion<'_, '_> {
+    /// Returns the fraction of the explosion at `origin` that reaches `body` at `target`,
+    /// in the range `[0, 1]`. Level geometry blocks the explosion, while [`BlastShield`]s weaken it.
+    fn exposure(
+        &self,
+        spatial_query: &SpatialQuery,
+        source: Entity,
+        body: Entity,
+        origin: Vec3,
+        target: Vec3,
+    ) -> f32 {
+        let Ok((direction, distance)) = Dir3::new_and_length(target - origin) else {
+            // The target is at the center of the explosion.
+            return 1.0;
+        };
+        let filter = SpatialQueryFilter::from_mask([CollisionLayer::Default, CollisionLayer::Prop]);
+        let hits = spatial_query.ray_hits(origin, direction, distance, u32::MAX, true, &filter);
+
+        let mut exposure = 1.0;
+        let mut shields = Vec::new();
+        for hit in hits {
+            let hit_body = self
+                .collider_of_query
+                .get(hit.entity)
+                .map_or(hit.entity, |collider_of| collider_of.body);
+            if hit_body == source || hit_body == body {
+                continue;
+            }
+            if let Ok(shield) = self.shield_query.get(hit_body) {
+                // A shield made of multiple colliders only absorbs the explosion once.
+                if !shields.contains(&hit_body) {
+                    shields.push(hit_body);
+                    exposure *= 1.0 - shield.absorption.clamp(0.0, 1.0);
+                }
+                continue;
+            }
+            // Colliders without explicit layers are part of the default layer.
+            let is_level_geometry = self
+                .layers_query
+                .get(hit.entity)
+                .ok()
+                .is_none_or(|layers| layers.memberships.has_all(CollisionLayer::Default));
+            if is_level_geometry {
+                return 0.0;
+            }
+        }
+        exposure
+    }
+}
+
 impl ExplosionHelper<'_, '_> {
     /// Applies an explosion to all entities within the explosion radius at the given point.
     ///
     /// This also triggers the [`OnExplode`] event for any explosive entities hit by the explosion.
-    pub(crate) fn apply_explosion(&mut self, explosive: &Explosive, point: Vec3) {
+    /// Bodies behind level geometry or [`BlastShield`]s are partially or fully protected.
+    /// The `source` body of the explosion never occludes it.
+    ///
+    /// Entities damaged by the explosion are marked with the next link of the `chain`,
+    /// so that explosions and deaths caused by the damage continue the chain.
+    pub(crate) fn apply_explosion(
+        &mut self,
+        source: Entity,
+        explosive: &Explosive,
+        point: Vec3,
+        chain: ChainLink,
+    ) {
         // Query for all collider entities of characters and props within the explosion radius.
         let shape = Collider::sphere(explosive.radius);
         let filter = SpatialQueryFilter::default();
@@ -295,17 +486,64 @@ impl ExplosionHelper<'_, '_> {
             };
             let global_com = transform.translation() + transform.rotation() * local_com.0;
 
+            // Iterate over the colliders of the body to find the point closest
+            // to the source of the explosion.
+            let (closest_point, is_inside) = self
+                .collider_query
+                .iter_many(colliders.iter())
+                .map(|(collider, transform)| {
+                    collider.project_point(
+                        transform.translation(),
+                        transform.rotation(),
+                        point,
+                        true,
+                    )
+                })
+                .min_by(|(a, _), (b, _)| {
+                    a.distance_squared(point)
+                        .partial_cmp(&b.distance_squared(point))
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .expect("Body hit by explosion has no colliders. Huh???");
+
+            // Both the damage and the impulse fall off with the distance to the closest point.
+            let distance = if is_inside {
+                0.0
+            } else {
+                closest_point.distance(point)
+            };
+            let falloff = explosive.falloff_at(distance);
+            if falloff <= 0.0 {
+                continue;
+            }
+
+            // Check whether the body is hidden from the explosion. We test both the closest point
+            // and the center of mass, so that a body peeking out from behind cover is still hit.
+            let exposure = [closest_point, global_com]
+                .into_iter()
+                .map(|target| {
+                    self.occlusion
+                        .exposure(&self.spatial_query, source, body, point, target)
+                })
+                .fold(0.0, f32::max);
+            let falloff = falloff * exposure;
+            if falloff <= 0.0 {
+                continue;
+            }
+
             // If the entity has health, we apply damage to it.
             if let Ok(is_player) = self.damageable_query.get(body) {
-                let mut damage = explosive.damage;
+                let mut damage = OnDamage::new(explosive.damage * falloff, DamageKind::Explosion)
+                    .with_instigator(source)
+                    .with_position(closest_point);
 
                 if is_player {
                     // If the explosive damages the player, we apply a scaled damage immediately.
                     if !explosive.damages_player {
                         continue;
                     }
-                    damage *= EXPLOSION_PLAYER_DAMAGE_SCALE;
-                    self.commands.entity(body).trigger(OnDamage(damage));
+                    damage.amount *= EXPLOSION_PLAYER_DAMAGE_SCALE;
+                    self.commands.entity(body).trigger(damage);
                 } else {
                     // For damage against enemies or explosives, we use a small delay.
                     let delay = 0.2;
@@ -314,7 +552,10 @@ impl ExplosionHelper<'_, '_> {
                         .try_insert_if_new(AutoTimer(Timer::from_seconds(delay, TimerMode::Once)))
                         .observe(
                             move |trigger: Trigger<OnAutoTimerFinish>, mut commands: Commands| {
-                                commands.entity(trigger.target()).trigger(OnDamage(damage));
+                                commands
+                                    .entity(trigger.target())
+                                    .try_insert(chain.next())
+                                    .trigger(damage);
                             },
                         );
                 }
@@ -324,31 +565,10 @@ impl ExplosionHelper<'_, '_> {
                 continue;
             }
 
-            // Iterate over the colliders of the body to find the point closest
-            // to the source of the explosion.
-            let (closest_point, _is_inside) = self
-                .collider_query
-                .iter_many(colliders.iter())
-                .map(|(collider, transform)| {
-                    collider.project_point(
-                        transform.translation(),
-                        transform.rotation(),
-                        point,
-                        true,
-                    )
-                })
-                .min_by(|(a, _), (b, _)| {
-                    a.distance_squared(point)
-                        .partial_cmp(&b.distance_squared(point))
-                        .unwrap_or(std::cmp::Ordering::Equal)
-                })
-                .expect("Body hit by explosion has no colliders. Huh???");
-
             // Compute the impulse direction and magnitude.
             // We ignore mass properties here to make explosions more predictable and fun.
-            // TODO: We could support a falloff based on the distance from the center of the explosion.
             let explosion_direction = (closest_point - point).normalize_or_zero();
-            let lin_impulse = explosive.impulse_strength * explosion_direction;
+            let lin_impulse = explosive.impulse_strength * falloff * explosion_direction;
             let ang_impulse = (closest_point - global_com).cross(lin_impulse);
 
             // Apply the impulses to the body's velocities.
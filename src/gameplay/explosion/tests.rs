@@ -0,0 +1,92 @@
+use std::time::Duration;
+
+use avian3d::prelude::*;
+use bevy::{platform::collections::HashSet, prelude::*};
+
+use super::{ExplosionFalloff, Explosive, OnExplode};
+use crate::headless::{headless_app, run_for, run_updates, spawn_barrel_row};
+
+#[derive(Resource, Default)]
+struct ExplodedEntities(HashSet<Entity>);
+
+#[test]
+fn barrel_chain_explodes_completely() {
+    let mut app = headless_app();
+    app.init_resource::<ExplodedEntities>();
+    app.add_observer(
+        |trigger: Trigger<OnExplode>, mut exploded: ResMut<ExplodedEntities>| {
+            exploded.0.insert(trigger.target());
+        },
+    );
+
+    // Each barrel is within the explosion radius of its neighbors, but not of the ones after.
+    let barrels = spawn_barrel_row(&mut app, 5, 2.5);
+    // Let physics pick up the colliders before querying them.
+    run_updates(&mut app, 2);
+
+    app.world_mut()
+        .trigger_targets(OnExplode::default(), barrels[0]);
+    // Every link of the chain delays the damage of the next barrel a bit.
+    run_for(&mut app, Duration::from_secs(3));
+
+    let exploded = &app.world().resource::<ExplodedEntities>().0;
+    for (i, barrel) in barrels.iter().enumerate() {
+        assert!(exploded.contains(barrel), "Barrel {i} did not explode");
+        assert!(
+            app.world().get_entity(*barrel).is_err(),
+            "Barrel {i} was not despawned"
+        );
+    }
+}
+
+#[test]
+fn falloff_goes_from_full_at_inner_radius_to_none_at_outer_radius() {
+    let custom = "1, 0.25, 0".parse::<ExplosionFalloff>().unwrap();
+    for falloff in [
+        ExplosionFalloff::Linear,
+        ExplosionFalloff::InverseSquare,
+        custom,
+    ] {
+        let explosive = Explosive {
+            radius: 4.0,
+            inner_radius: 1.0,
+            falloff: falloff.clone(),
+            ..default()
+        };
+        assert_eq!(explosive.falloff_at(0.5), 1.0, "{falloff:?}");
+        assert_eq!(explosive.falloff_at(1.0), 1.0, "{falloff:?}");
+        assert_eq!(explosive.falloff_at(4.0), 0.0, "{falloff:?}");
+        let halfway = explosive.falloff_at(2.5);
+        assert!(0.0 < halfway && halfway < 1.0, "{falloff:?}: {halfway}");
+    }
+}
+
+#[test]
+fn walls_block_explosions() {
+    let mut app = headless_app();
+    app.init_resource::<ExplodedEntities>();
+    app.add_observer(
+        |trigger: Trigger<OnExplode>, mut exploded: ResMut<ExplodedEntities>| {
+            exploded.0.insert(trigger.target());
+        },
+    );
+
+    let barrels = spawn_barrel_row(&mut app, 2, 2.5);
+    app.world_mut().spawn((
+        RigidBody::Static,
+        Collider::cuboid(0.2, 4.0, 4.0),
+        Transform::from_xyz(1.25, 0.0, 0.0),
+    ));
+    run_updates(&mut app, 2);
+
+    app.world_mut()
+        .trigger_targets(OnExplode::default(), barrels[0]);
+    run_for(&mut app, Duration::from_secs(1));
+
+    let exploded = &app.world().resource::<ExplodedEntities>().0;
+    assert!(exploded.contains(&barrels[0]));
+    assert!(
+        !exploded.contains(&barrels[1]),
+        "The barrel behind the wall exploded"
+    );
+}
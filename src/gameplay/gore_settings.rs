@@ -2,6 +2,7 @@ use std::time::Duration;
 
 use bevy::prelude::*;
 use bevy_mesh_decal::Decal;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     despawn_after::{DespawnAfter, FadeOutAndDespawn},
@@ -21,8 +22,9 @@ pub(super) fn plugin(app: &mut App) {
     );
 }
 
-#[derive(Resource, Reflect, Clone)]
+#[derive(Resource, Reflect, Clone, Serialize, Deserialize)]
 #[reflect(Resource)]
+#[serde(default)]
 pub(crate) struct GoreSettings {
     pub blood_decals: Gore,
     pub gibs: Gore,
@@ -45,7 +47,7 @@ impl Default for GoreSettings {
     }
 }
 
-#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Gore {
     None,
     NeverDespawn,
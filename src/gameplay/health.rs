@@ -2,14 +2,15 @@ use bevy::prelude::*;
 
 use crate::{PostPhysicsAppSystems, screens::Screen};
 
-pub(super) fn plugin(app: &mut App) {
-    app.register_type::<Health>();
+pub(crate) fn plugin(app: &mut App) {
+    app.register_type::<(Health, DamageKind, Armor, Shield, Resistances)>();
     app.add_systems(
         Update,
         kill_out_of_bounds
             .in_set(PostPhysicsAppSystems::TriggerDeath)
             .run_if(in_state(Screen::Gameplay)),
     );
+    app.add_systems(Update, regenerate_shields);
     app.add_observer(on_damage);
 }
 
@@ -52,27 +53,271 @@ impl Default for Health {
     }
 }
 
-fn on_damage(trigger: Trigger<OnDamage>, mut health: Query<&mut Health>, mut commands: Commands) {
+/// Absorbs a fraction of the damage dealt to [`Health`], wearing down in the process.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub(crate) struct Armor {
+    pub(crate) current: f32,
+    pub(crate) max: f32,
+    /// The fraction of incoming damage the armor takes instead of the health, from 0 to 1.
+    pub(crate) absorption: f32,
+}
+
+impl Armor {
+    pub(crate) fn new(max: f32, absorption: f32) -> Self {
+        Self {
+            current: max,
+            max,
+            absorption,
+        }
+    }
+
+    /// Returns the damage that gets through the armor.
+    fn absorb(&mut self, amount: f32) -> f32 {
+        let absorbed = (amount * self.absorption.clamp(0.0, 1.0)).min(self.current);
+        self.current -= absorbed;
+        amount - absorbed
+    }
+}
+
+/// Takes all damage before [`Armor`] and [`Health`] do, and regenerates when no damage was taken for a while.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub(crate) struct Shield {
+    pub(crate) current: f32,
+    pub(crate) max: f32,
+    /// How long after taking damage the shield starts regenerating, in seconds.
+    pub(crate) regen_delay: f32,
+    /// How much the shield regenerates per second.
+    pub(crate) regen_rate: f32,
+    since_damage: f32,
+}
+
+impl Shield {
+    pub(crate) fn new(max: f32) -> Self {
+        Self {
+            current: max,
+            max,
+            regen_delay: 3.0,
+            regen_rate: max / 2.0,
+            since_damage: 0.0,
+        }
+    }
+
+    /// Returns the damage that gets through the shield.
+    fn absorb(&mut self, amount: f32) -> f32 {
+        self.since_damage = 0.0;
+        let absorbed = amount.min(self.current);
+        self.current -= absorbed;
+        amount - absorbed
+    }
+}
+
+/// Reduces damage of specific [`DamageKind`]s. Each value is the fraction of damage ignored, from 0 to 1.
+#[derive(Component, Debug, Clone, Default, Reflect)]
+#[reflect(Component)]
+pub(crate) struct Resistances {
+    pub(crate) bullet: f32,
+    pub(crate) explosion: f32,
+    pub(crate) melee: f32,
+    pub(crate) acid: f32,
+    pub(crate) fall: f32,
+    pub(crate) environment: f32,
+}
+
+impl Resistances {
+    pub(crate) fn get(&self, kind: DamageKind) -> f32 {
+        match kind {
+            DamageKind::Bullet => self.bullet,
+            DamageKind::Explosion => self.explosion,
+            DamageKind::Melee => self.melee,
+            DamageKind::Acid => self.acid,
+            DamageKind::Fall => self.fall,
+            DamageKind::Environment => self.environment,
+            DamageKind::Despawn => 0.0,
+        }
+    }
+}
+
+fn on_damage(
+    trigger: Trigger<OnDamage>,
+    mut health: Query<(
+        &mut Health,
+        Option<&Resistances>,
+        Option<&mut Shield>,
+        Option<&mut Armor>,
+    )>,
+    mut commands: Commands,
+) {
     let entity = trigger.target();
-    let Ok(mut health) = health.get_mut(entity) else {
+    let Ok((mut health, resistances, shield, armor)) = health.get_mut(entity) else {
         return;
     };
-    health.damage(trigger.event().0);
+    let mut amount = trigger.amount;
+    // Despawning must always kill.
+    if trigger.kind != DamageKind::Despawn {
+        if let Some(resistances) = resistances {
+            amount *= 1.0 - resistances.get(trigger.kind).clamp(0.0, 1.0);
+        }
+        if let Some(mut shield) = shield {
+            amount = shield.absorb(amount);
+        }
+        if let Some(mut armor) = armor {
+            amount = armor.absorb(amount);
+        }
+    }
+    health.damage(amount);
     if health.is_dead() {
-        commands.entity(entity).remove::<Health>().trigger(OnDeath);
+        commands
+            .entity(entity)
+            .remove::<Health>()
+            .trigger(OnDeath::from(*trigger.event()));
     }
 }
 
-#[derive(Debug, Event)]
-pub(crate) struct OnDamage(pub(crate) f32);
+/// What dealt damage to an entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub(crate) enum DamageKind {
+    Bullet,
+    Explosion,
+    Melee,
+    /// Spit from ranged enemies and the acid puddles it leaves behind.
+    Acid,
+    Fall,
+    /// Damage from the level itself, like falling out of bounds.
+    Environment,
+    /// Entities that are removed by the game, like NPCs that got stuck.
+    Despawn,
+}
+
+/// An event that is triggered on an entity to damage it.
+#[derive(Debug, Clone, Copy, Event)]
+pub(crate) struct OnDamage {
+    pub(crate) amount: f32,
+    pub(crate) kind: DamageKind,
+    /// The entity that dealt the damage, such as the player for bullets or the explosive for explosions.
+    pub(crate) instigator: Option<Entity>,
+    /// Where the damage was dealt, in world space.
+    pub(crate) position: Option<Vec3>,
+}
+
+impl OnDamage {
+    pub(crate) fn new(amount: f32, kind: DamageKind) -> Self {
+        Self {
+            amount,
+            kind,
+            instigator: None,
+            position: None,
+        }
+    }
+
+    pub(crate) fn with_instigator(mut self, instigator: Entity) -> Self {
+        self.instigator = Some(instigator);
+        self
+    }
 
-#[derive(Debug, Event)]
-pub(crate) struct OnDeath;
+    pub(crate) fn with_position(mut self, position: Vec3) -> Self {
+        self.position = Some(position);
+        self
+    }
+}
+
+/// An event that is triggered on an entity when it dies, with the damage that killed it.
+#[derive(Debug, Clone, Copy, Event)]
+pub(crate) struct OnDeath {
+    pub(crate) kind: DamageKind,
+    pub(crate) instigator: Option<Entity>,
+    pub(crate) position: Option<Vec3>,
+}
+
+impl From<OnDamage> for OnDeath {
+    fn from(damage: OnDamage) -> Self {
+        Self {
+            kind: damage.kind,
+            instigator: damage.instigator,
+            position: damage.position,
+        }
+    }
+}
+
+fn regenerate_shields(mut shields: Query<&mut Shield>, time: Res<Time>) {
+    for mut shield in &mut shields {
+        if shield.current >= shield.max {
+            continue;
+        }
+        shield.since_damage += time.delta_secs();
+        if shield.since_damage >= shield.regen_delay {
+            shield.current =
+                (shield.current + shield.regen_rate * time.delta_secs()).min(shield.max);
+        }
+    }
+}
 
 fn kill_out_of_bounds(health: Query<(Entity, &Transform)>, mut commands: Commands) {
     for (entity, transform) in health.iter() {
         if transform.translation.y < -300.0 {
-            commands.entity(entity).trigger(OnDeath);
+            commands.entity(entity).trigger(OnDeath {
+                kind: DamageKind::Environment,
+                instigator: None,
+                position: Some(transform.translation),
+            });
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headless::{headless_app, run_updates};
+
+    #[derive(Resource, Default)]
+    struct Deaths(usize);
+
+    #[test]
+    fn damage_past_zero_triggers_death_exactly_once() {
+        let mut app = headless_app();
+        app.init_resource::<Deaths>();
+        app.add_observer(|_trigger: Trigger<OnDeath>, mut deaths: ResMut<Deaths>| {
+            deaths.0 += 1;
+        });
+        run_updates(&mut app, 1);
+
+        let entity = app.world_mut().spawn(Health::new(10.0)).id();
+        for damage in [6.0, 6.0, 6.0] {
+            app.world_mut()
+                .trigger_targets(OnDamage::new(damage, DamageKind::Bullet), entity);
+            run_updates(&mut app, 1);
+        }
+
+        assert_eq!(app.world().resource::<Deaths>().0, 1);
+        assert!(app.world().get::<Health>(entity).is_none());
+    }
+
+    #[test]
+    fn defenses_absorb_damage_in_order() {
+        let mut app = headless_app();
+        run_updates(&mut app, 1);
+
+        let entity = app
+            .world_mut()
+            .spawn((
+                Health::new(100.0),
+                Shield::new(10.0),
+                Armor::new(20.0, 0.5),
+                Resistances {
+                    explosion: 0.5,
+                    ..default()
+                },
+            ))
+            .id();
+        // Halved by the resistance, then the shield takes 10 and the armor half of the remaining 20.
+        app.world_mut()
+            .trigger_targets(OnDamage::new(60.0, DamageKind::Explosion), entity);
+        run_updates(&mut app, 1);
+
+        let world = app.world();
+        assert_eq!(world.get::<Shield>(entity).unwrap().current, 0.0);
+        assert_eq!(world.get::<Armor>(entity).unwrap().current, 10.0);
+        assert_eq!(world.get::<Health>(entity).unwrap().current, 90.0);
+    }
+}
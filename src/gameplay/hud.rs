@@ -5,9 +5,12 @@ use bevy_simple_subsecond_system::hot;
 
 use crate::asset_tracking::LoadResource;
 use crate::font::FontAssets;
-use crate::gameplay::health::{Health, OnDeath};
+use crate::gameplay::combo::Chains;
+use crate::gameplay::health::{Armor, Health, OnDeath, Shield};
 use crate::gameplay::npc::Npc;
 use crate::gameplay::player::Player;
+use crate::gameplay::player::grenades::Grenades;
+use crate::gameplay::player::weapons::{WeaponInventory, ammo::Reloading};
 use crate::gameplay::upgrades::Upgrades;
 use crate::gameplay::waves::{
     GameMode, WaveAdvanced, WaveFinishedPreparing, WaveStartedPreparing, Waves,
@@ -19,19 +22,29 @@ pub(super) fn plugin(app: &mut App) {
     app.load_resource::<HudAssets>();
     app.add_systems(
         OnEnter(Screen::Gameplay),
-        (spawn_health_bar, spawn_wave_hud),
+        (
+            spawn_health_bar,
+            spawn_wave_hud,
+            spawn_combo_hud,
+            spawn_ammo_hud,
+        ),
     );
     app.add_systems(
         Update,
         (
             update_health_bar,
+            update_defense_bars,
             update_prep_time_text,
             update_wave_text,
             blink_upgrade_menu_text,
+            update_combo_text,
+            update_ammo_text,
         ),
     );
-    app.register_type::<HealthBar>();
+    app.register_type::<(HealthBar, ShieldBar, ArmorBar)>();
     app.register_type::<WaveText>();
+    app.register_type::<ComboText>();
+    app.register_type::<AmmoText>();
     app.add_observer(add_angry_icon);
     app.add_observer(add_dead_icon);
     app.add_observer(flush_on_wave_advanced);
@@ -79,6 +92,16 @@ impl FromWorld for HudAssets {
 #[reflect(Component)]
 pub(crate) struct HealthBar;
 
+/// The fill of the bar above the [`HealthBar`] showing the player's [`Shield`].
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub(crate) struct ShieldBar;
+
+/// The fill of the bar above the [`HealthBar`] showing the player's [`Armor`].
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub(crate) struct ArmorBar;
+
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 pub(crate) struct WaveText;
@@ -99,6 +122,14 @@ pub(crate) struct PrepTimeText;
 #[reflect(Component)]
 pub(crate) struct UpgradeMenuText(Timer);
 
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub(crate) struct ComboText;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub(crate) struct AmmoText;
+
 fn spawn_wave_hud(mut commands: Commands, fonts: Res<FontAssets>, game_mode: Res<State<GameMode>>) {
     commands.spawn((
         Name::new("Spawn Wave HUD"),
@@ -322,52 +353,83 @@ fn spawn_health_bar(
             position_type: PositionType::Absolute,
             width: Percent(100.0),
             height: Percent(100.0),
-            align_items: AlignItems::End,
-            justify_content: JustifyContent::Center,
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::End,
+            row_gap: Px(4.0),
             bottom: Px(20.0),
             ..default()
         },
         Pickable::IGNORE,
-        children![(
-            Node {
-                width: Percent(100.0),
-                max_width: Px(500.0),
-                height: Px(15.0),
-                ..default()
-            },
-            BorderRadius::MAX,
-            BackgroundColor(Color::from(tailwind::ZINC_900.with_alpha(0.8))),
-            children![(
-                HealthBar,
+        children![
+            defense_bar(ShieldBar, tailwind::SKY_400),
+            defense_bar(ArmorBar, tailwind::ZINC_400),
+            (
                 Node {
-                    width: Percent(hp * 100.0),
-                    height: Percent(100.0),
-                    overflow: Overflow::clip(),
+                    width: Percent(100.0),
+                    max_width: Px(500.0),
+                    height: Px(15.0),
                     ..default()
                 },
-                BorderRadius::all(Px(10.0)),
-                BackgroundColor(Color::from(tailwind::RED_600.with_alpha(0.5))),
+                BorderRadius::MAX,
+                BackgroundColor(Color::from(tailwind::ZINC_900.with_alpha(0.8))),
                 children![(
-                    ImageNode {
-                        color: tailwind::RED_400.with_alpha(0.75).into(),
-                        image: hud_assets.health_bar_texture.clone(),
-                        image_mode: NodeImageMode::Auto,
-                        ..default()
-                    },
+                    HealthBar,
                     Node {
-                        position_type: PositionType::Absolute,
-                        top: Px(-250.0),
-                        left: Px(0.0),
-                        width: Px(500.0),
-                        height: Px(500.0),
+                        width: Percent(hp * 100.0),
+                        height: Percent(100.0),
+                        overflow: Overflow::clip(),
                         ..default()
                     },
-                ),]
-            )],
-        )],
+                    BorderRadius::all(Px(10.0)),
+                    BackgroundColor(Color::from(tailwind::RED_600.with_alpha(0.5))),
+                    children![(
+                        ImageNode {
+                            color: tailwind::RED_400.with_alpha(0.75).into(),
+                            image: hud_assets.health_bar_texture.clone(),
+                            image_mode: NodeImageMode::Auto,
+                            ..default()
+                        },
+                        Node {
+                            position_type: PositionType::Absolute,
+                            top: Px(-250.0),
+                            left: Px(0.0),
+                            width: Px(500.0),
+                            height: Px(500.0),
+                            ..default()
+                        },
+                    ),]
+                )],
+            )
+        ],
     ));
 }
 
+/// A thin bar for one of the extra layers on top of the player's health. Hidden while the player has none of it.
+fn defense_bar(marker: impl Component, color: Srgba) -> impl Bundle {
+    (
+        Node {
+            width: Percent(100.0),
+            max_width: Px(500.0),
+            height: Px(6.0),
+            display: Display::None,
+            ..default()
+        },
+        BorderRadius::MAX,
+        BackgroundColor(Color::from(tailwind::ZINC_900.with_alpha(0.8))),
+        children![(
+            marker,
+            Node {
+                width: Percent(0.0),
+                height: Percent(100.0),
+                ..default()
+            },
+            BorderRadius::MAX,
+            BackgroundColor(Color::from(color.with_alpha(0.7))),
+        )],
+    )
+}
+
 fn update_health_bar(
     health: Single<Option<&Health>, With<Player>>,
     mut health_bar: Single<&mut Node, With<HealthBar>>,
@@ -375,3 +437,108 @@ fn update_health_bar(
     let hp = health.map(|h| h.fraction()).unwrap_or(0.0);
     health_bar.width = Percent(hp * 100.0);
 }
+
+fn update_defense_bars(
+    player: Single<(Option<&Shield>, Option<&Armor>), With<Player>>,
+    mut fills: Query<(&mut Node, &ChildOf, Has<ShieldBar>), Or<(With<ShieldBar>, With<ArmorBar>)>>,
+    mut tracks: Query<&mut Node, (Without<ShieldBar>, Without<ArmorBar>)>,
+) {
+    let (shield, armor) = player.into_inner();
+    for (mut fill, child_of, is_shield) in &mut fills {
+        let (current, max) = if is_shield {
+            shield.map_or((0.0, 0.0), |shield| (shield.current, shield.max))
+        } else {
+            armor.map_or((0.0, 0.0), |armor| (armor.current, armor.max))
+        };
+        let Ok(mut track) = tracks.get_mut(child_of.parent()) else {
+            continue;
+        };
+        if max <= 0.0 {
+            track.display = Display::None;
+            continue;
+        }
+        track.display = Display::Flex;
+        fill.width = Percent(current / max * 100.0);
+    }
+}
+
+fn spawn_combo_hud(mut commands: Commands, fonts: Res<FontAssets>) {
+    commands.spawn((
+        Name::new("Combo HUD"),
+        StateScoped(Screen::Gameplay),
+        Node {
+            position_type: PositionType::Absolute,
+            right: Px(30.0),
+            top: Px(120.0),
+            ..default()
+        },
+        Pickable::IGNORE,
+        children![(
+            Text::new(""),
+            TextFont::from_font_size(32.0).with_font(fonts.default.clone()),
+            TextColor(Color::from(tailwind::ORANGE_400)),
+            ComboText,
+        )],
+    ));
+}
+
+fn update_combo_text(chains: Res<Chains>, mut combo_text: Single<&mut Text, With<ComboText>>) {
+    // Single explosions are not worth mentioning.
+    ***combo_text = match chains.current() {
+        Some(chain) if chain.explosions > 1 => format!(
+            "Chain x{}\n{} kills  x{:.1}  +{}",
+            chain.explosions,
+            chain.kills,
+            chain.multiplier(),
+            chain.score()
+        ),
+        _ => String::new(),
+    };
+}
+
+fn spawn_ammo_hud(mut commands: Commands, fonts: Res<FontAssets>) {
+    commands.spawn((
+        Name::new("Ammo HUD"),
+        StateScoped(Screen::Gameplay),
+        Node {
+            position_type: PositionType::Absolute,
+            right: Px(30.0),
+            bottom: Px(30.0),
+            ..default()
+        },
+        Pickable::IGNORE,
+        children![(
+            Text::new(""),
+            TextFont::from_font_size(26.0).with_font(fonts.default.clone()),
+            TextLayout::new_with_justify(JustifyText::Right),
+            AmmoText,
+        )],
+    ));
+}
+
+fn update_ammo_text(
+    player: Single<(&WeaponInventory, &Grenades, Has<Reloading>), With<Player>>,
+    mut ammo_text: Single<&mut Text, With<AmmoText>>,
+) {
+    let (inventory, grenades, reloading) = player.into_inner();
+    let slot = inventory.selected();
+    let empty = slot.stats.magazine_size.saturating_sub(slot.ammo.loaded);
+    let shells = "█".repeat(slot.ammo.loaded as usize) + &"_".repeat(empty as usize);
+    let status = if reloading {
+        "Reloading..."
+    } else if slot.ammo.loaded == 0 && slot.ammo.reserve == 0 {
+        "Out of ammo"
+    } else {
+        ""
+    };
+    let text = format!(
+        "{status}\n{}\n{shells}  {} | {}\nGrenades  {}",
+        slot.weapon.definition().name,
+        slot.ammo.loaded,
+        slot.ammo.reserve,
+        "●".repeat(grenades.count as usize),
+    );
+    if ***ammo_text != text {
+        ***ammo_text = text;
+    }
+}
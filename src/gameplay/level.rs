@@ -4,7 +4,10 @@ use bevy::prelude::*;
 #[cfg(feature = "hot_patch")]
 use bevy_simple_subsecond_system::hot;
 
-use crate::{asset_tracking::LoadResource, audio::music, gameplay::waves::Waves, screens::Screen};
+use crate::{
+    asset_tracking::LoadResource, audio::music, gameplay::waves::assets::spawn_level_waves,
+    screens::Screen,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.load_resource::<LevelAssets>();
@@ -14,18 +17,15 @@ pub(super) fn plugin(app: &mut App) {
 /// A system that spawns the main level.
 #[cfg_attr(feature = "hot_patch", hot)]
 pub(crate) fn spawn_level(mut commands: Commands, level_assets: Res<LevelAssets>) {
-    commands.spawn((
-        Name::new("Level"),
-        SceneRoot(level_assets.level.clone()),
-        StateScoped(Screen::Gameplay),
-        Level,
-        children![(Name::new("Level Music"), music(level_assets.music.clone()))],
-    ));
-    commands.spawn((
-        Name::new("Waves"),
-        StateScoped(Screen::Gameplay),
-        Waves::default(),
-    ));
+    commands
+        .spawn((
+            Name::new("Level"),
+            SceneRoot(level_assets.level.clone()),
+            StateScoped(Screen::Gameplay),
+            Level,
+            children![(Name::new("Level Music"), music(level_assets.music.clone()))],
+        ))
+        .observe(spawn_level_waves);
     commands.insert_resource(AmbientLight::NONE);
 }
 
@@ -6,6 +6,7 @@
 use bevy::prelude::*;
 
 mod animation;
+pub(crate) mod combo;
 pub(crate) mod crosshair;
 pub(crate) mod explosion;
 pub(crate) mod gore_settings;
@@ -14,6 +15,9 @@ pub(crate) mod hud;
 pub(crate) mod level;
 pub(crate) mod npc;
 pub(crate) mod player;
+pub(crate) mod replay;
+pub(crate) mod rng;
+pub(crate) mod score;
 pub(crate) mod time;
 pub(crate) mod upgrades;
 pub(crate) mod waves;
@@ -21,6 +25,7 @@ pub(crate) mod waves;
 pub(super) fn plugin(app: &mut App) {
     app.add_plugins((
         animation::plugin,
+        combo::plugin,
         crosshair::plugin,
         explosion::plugin,
         gore_settings::plugin,
@@ -30,6 +35,7 @@ pub(super) fn plugin(app: &mut App) {
         hud::plugin,
         waves::plugin,
         time::plugin,
+        (rng::plugin, replay::plugin, score::plugin),
         upgrades::plugin,
         // This plugin preloads the level,
         // so make sure to add it last.
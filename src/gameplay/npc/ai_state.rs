@@ -1,18 +1,35 @@
+//! What NPCs are currently doing.
+//!
+//! Every frame, NPCs that are not in the middle of something ask their [`Behavior`](super::behavior::Behavior)
+//! what to do next and switch to the corresponding [`AiState`], which [navigation](super::navigation) and
+//! [attacks](super::attack) then act on.
+
+use std::ops::Range;
+
+use avian3d::prelude::*;
 use bevy::{
     audio::{SpatialScale, Volume},
     prelude::*,
 };
-use bevy_landmass::AgentState;
+use bevy_landmass::{AgentState, TargetReachedCondition};
 #[cfg(feature = "hot_patch")]
 use bevy_simple_subsecond_system::hot;
-use rand::Rng as _;
+use rand::Rng;
 
 use crate::{
     PostPhysicsAppSystems,
     audio::SoundEffect,
     gameplay::{
-        npc::{assets::NpcAssets, stats::NpcStats},
+        npc::{
+            assets::NpcAssets,
+            behavior::{Action, Context, Decision},
+            links::UsingNavLink,
+            perception::{Perception, eye_position, has_line_of_sight, perceive},
+            spit::{OnSpit, SpitCooldown},
+            stats::NpcStats,
+        },
         player::Player,
+        rng::{GameplayRng, RngStream},
     },
 };
 
@@ -20,20 +37,64 @@ use super::{attack::Attacking, navigation::Agent};
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<AiState>();
-    app.add_systems(PreUpdate, update_ai_state);
+    app.add_systems(PreUpdate, update_ai_state.after(perceive));
     app.add_systems(
         Update,
-        update_stagger_timer.in_set(PostPhysicsAppSystems::TickTimers),
+        tick_ai_state_timers.in_set(PostPhysicsAppSystems::TickTimers),
     );
 }
 
+/// How long a ranged NPC backs away from the player before reconsidering.
+const REPOSITION_SECS: f32 = 1.5;
+/// How long an NPC stares at the player after noticing them, before it starts chasing.
+const ALERT_SECS: f32 = 0.4;
+/// How long an unaware NPC stands around before wandering off, in seconds.
+const IDLE_SECS: Range<f32> = 2.0..5.0;
+/// How far an unaware NPC wanders at a time.
+const WANDER_RADIUS: f32 = 8.0;
+/// How close an NPC has to get to the point it is wandering to or investigating.
+const ARRIVE_DISTANCE: f32 = 2.0;
+
 #[derive(Component, Debug, Default, Reflect, Clone)]
 #[reflect(Component)]
 pub(crate) enum AiState {
+    /// Standing around, unaware of the player.
+    Idle(Timer),
+    /// Strolling to a random point, unaware of the player.
+    Wander(Vec3),
+    /// Going to where the player was last seen or heard.
+    Investigate(Vec3),
+    /// Just noticed the player and turns towards them before chasing.
+    Alerted(Timer),
     #[default]
     Chase,
     Stagger(Timer),
     Attack,
+    /// A ranged NPC stands still and aims at the player, spitting when the timer finishes.
+    Aim(Timer),
+    /// A ranged NPC backs away from the player because they got too close.
+    Reposition(Timer),
+}
+
+impl AiState {
+    pub(crate) fn alerted() -> Self {
+        Self::Alerted(Timer::from_seconds(ALERT_SECS, TimerMode::Once))
+    }
+
+    pub(crate) fn idle(rng: &mut impl Rng) -> Self {
+        Self::Idle(Timer::from_seconds(
+            rng.gen_range(IDLE_SECS),
+            TimerMode::Once,
+        ))
+    }
+
+    /// Whether the NPC is not after the player.
+    pub(crate) fn is_unaware(&self) -> bool {
+        matches!(
+            self,
+            Self::Idle(..) | Self::Wander(..) | Self::Investigate(..)
+        )
+    }
 }
 
 #[cfg_attr(feature = "hot_patch", hot)]
@@ -41,68 +102,203 @@ fn update_ai_state(
     mut ai_state: Query<(
         Entity,
         &mut AiState,
+        &mut Perception,
+        &mut Decision,
         &NpcStats,
         &Agent,
         &Transform,
         Has<Attacking>,
+        Has<SpitCooldown>,
+        Has<UsingNavLink>,
     )>,
     player: Single<&Transform, With<Player>>,
     agent_state: Query<&AgentState>,
+    mut target_reached: Query<&mut TargetReachedCondition>,
+    spatial_query: SpatialQuery,
     mut npc_assets: ResMut<NpcAssets>,
+    mut rng: ResMut<GameplayRng>,
     mut commands: Commands,
 ) {
-    for (entity, mut ai_state, stats, agent, transform, attacking) in &mut ai_state {
+    for (
+        entity,
+        mut ai_state,
+        mut perception,
+        mut decision,
+        stats,
+        agent,
+        transform,
+        attacking,
+        spit_cooldown,
+        using_link,
+    ) in &mut ai_state
+    {
         let Ok(agent_state) = agent_state.get(**agent) else {
             continue;
         };
-        match ai_state.clone() {
-            AiState::Chase => {
-                if matches!(agent_state, AgentState::ReachedTarget) {
-                    *ai_state = AiState::Attack;
-                    let target = Vec3::new(
-                        player.translation.x,
-                        transform.translation.y,
-                        player.translation.z,
-                    );
-                    commands.entity(entity).insert(Attacking {
-                        dir: Dir3::try_from(target - transform.translation).ok(),
-                        speed: rand::thread_rng().gen_range(stats.attack_speed_range.clone()),
-                        damage: stats.attack_damage,
-                    });
-                    let handle = npc_assets
-                        .attack_sound
-                        .pick(&mut rand::thread_rng())
-                        .clone();
-                    let speed_mod = rand::thread_rng().gen_range(0.9..1.1);
-                    commands.spawn((
-                        *transform,
-                        AudioPlayer(handle),
-                        PlaybackSettings::DESPAWN
-                            .with_spatial(true)
-                            .with_volume(Volume::Linear(1.1))
-                            .with_speed(1.0 / stats.size * speed_mod)
-                            .with_spatial_scale(SpatialScale::new(1.0 / 7.5)),
-                        SoundEffect,
-                    ));
-                }
-            }
-            AiState::Stagger(timer) => {
+        let distance_to_player = transform.translation.distance(player.translation);
+
+        // Actions that take a while are seen through before deciding on the next one.
+        match &*ai_state {
+            AiState::Stagger(timer) | AiState::Alerted(timer) if !timer.finished() => continue,
+            AiState::Attack if attacking => continue,
+            AiState::Aim(timer) => {
                 if timer.finished() {
+                    commands.trigger_targets(OnSpit, entity);
                     *ai_state = AiState::Chase;
                 }
+                continue;
             }
-            AiState::Attack => {
-                if !attacking {
-                    *ai_state = AiState::Chase;
+            AiState::Reposition(timer) => {
+                let far_enough = stats
+                    .ranged
+                    .as_ref()
+                    .is_none_or(|ranged| distance_to_player >= ranged.preferred_distance);
+                if !timer.finished() && !far_enough {
+                    continue;
+                }
+            }
+            _ => {}
+        }
+
+        let player_in_sight = stats.ranged.is_some()
+            && has_line_of_sight(
+                &spatial_query,
+                eye_position(transform, stats),
+                player.translation,
+            );
+        if let Some(ranged) = &stats.ranged
+            && let Ok(mut condition) = target_reached.get_mut(**agent)
+        {
+            // Close in on the player until they are in sight.
+            let reach = if player_in_sight {
+                ranged.preferred_distance
+            } else {
+                1.5 * stats.size
+            };
+            *condition = TargetReachedCondition::Distance(Some(reach));
+        }
+        let reached_target = match &*ai_state {
+            AiState::Wander(point) | AiState::Investigate(point) => {
+                transform.translation.xz().distance(point.xz()) < ARRIVE_DISTANCE
+            }
+            // On the way over a link, the target is the link and not the player.
+            AiState::Chase => !using_link && matches!(agent_state, AgentState::ReachedTarget),
+            // The target of the navigation is the NPC itself.
+            _ => false,
+        };
+        let target_unreachable = matches!(
+            agent_state,
+            AgentState::NoPath | AgentState::TargetNotOnNavMesh
+        );
+        if matches!(*ai_state, AiState::Investigate(_)) && (reached_target || target_unreachable) {
+            // Nothing to see here.
+            perception.forget();
+        }
+
+        let context = Context {
+            state: &*ai_state,
+            perception: &*perception,
+            stats,
+            distance_to_player,
+            player_in_sight,
+            reached_target,
+            target_unreachable,
+            can_spit: !spit_cooldown,
+        };
+        let Some((action, score)) = stats.behavior.choose(&context) else {
+            continue;
+        };
+        *decision = Decision { action, score };
+        if Action::ongoing(&ai_state) == Some(action) && action != Action::Investigate {
+            continue;
+        }
+
+        match action {
+            Action::Idle => {
+                *ai_state = AiState::idle(rng.stream(RngStream::Npc));
+            }
+            Action::Wander => {
+                let offset = Circle::new(WANDER_RADIUS).sample_interior(rng.stream(RngStream::Npc));
+                *ai_state =
+                    AiState::Wander(transform.translation + Vec3::new(offset.x, 0.0, offset.y));
+            }
+            Action::Investigate => {
+                // Follow up on new clues, or keep going to the old ones.
+                let Some(target) = perception.last_known_position() else {
+                    continue;
+                };
+                if !matches!(*ai_state, AiState::Investigate(point) if point == target) {
+                    *ai_state = AiState::Investigate(target);
                 }
             }
+            Action::Alert => {
+                *ai_state = AiState::alerted();
+                let handle = npc_assets.idle_sound.pick(&mut rand::thread_rng()).clone();
+                commands.spawn(npc_sound(handle, transform, stats));
+            }
+            Action::Chase => {
+                *ai_state = AiState::Chase;
+            }
+            Action::Melee => {
+                *ai_state = AiState::Attack;
+                let target = Vec3::new(
+                    player.translation.x,
+                    transform.translation.y,
+                    player.translation.z,
+                );
+                commands.entity(entity).insert(Attacking {
+                    dir: Dir3::try_from(target - transform.translation).ok(),
+                    speed: rng
+                        .stream(RngStream::Npc)
+                        .gen_range(stats.attack_speed_range.clone()),
+                    damage: stats.attack_damage,
+                    attack: stats.attack.clone(),
+                });
+                let handle = npc_assets
+                    .attack_sound
+                    .pick(&mut rand::thread_rng())
+                    .clone();
+                commands.spawn(npc_sound(handle, transform, stats));
+            }
+            Action::Spit => {
+                let aim_secs = stats.ranged.as_ref().map_or(0.0, |ranged| ranged.aim_secs);
+                *ai_state = AiState::Aim(Timer::from_seconds(aim_secs, TimerMode::Once));
+                let handle = npc_assets
+                    .attack_sound
+                    .pick(&mut rand::thread_rng())
+                    .clone();
+                commands.spawn(npc_sound(handle, transform, stats));
+            }
+            Action::Reposition => {
+                *ai_state =
+                    AiState::Reposition(Timer::from_seconds(REPOSITION_SECS, TimerMode::Once));
+            }
         }
     }
 }
 
-fn update_stagger_timer(mut ai_state: Query<&mut AiState>, time: Res<Time>) {
+fn npc_sound(handle: Handle<AudioSource>, transform: &Transform, stats: &NpcStats) -> impl Bundle {
+    let speed_mod = rand::thread_rng().gen_range(0.9..1.1);
+    (
+        *transform,
+        AudioPlayer(handle),
+        PlaybackSettings::DESPAWN
+            .with_spatial(true)
+            .with_volume(Volume::Linear(1.1))
+            .with_speed(1.0 / stats.size * speed_mod)
+            .with_spatial_scale(SpatialScale::new(1.0 / 7.5)),
+        SoundEffect,
+    )
+}
+
+fn tick_ai_state_timers(mut ai_state: Query<&mut AiState>, time: Res<Time>) {
     for mut ai_state in &mut ai_state {
-        if let AiState::Stagger(ref mut timer) = *ai_state {
+        if let AiState::Stagger(ref mut timer)
+        | AiState::Idle(ref mut timer)
+        | AiState::Alerted(ref mut timer)
+        | AiState::Aim(ref mut timer)
+        | AiState::Reposition(ref mut timer) = *ai_state
+        {
             timer.tick(time.delta());
         }
     }
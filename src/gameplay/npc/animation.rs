@@ -10,7 +10,9 @@ use rand::Rng;
 
 use crate::{PostPhysicsAppSystems, gameplay::animation::AnimationPlayers};
 
-use super::{assets::NpcAssets, attack::Attacking};
+use super::{assets::NpcAssets, attack::Attacking, links::UsingNavLink};
+
+const CLIMB_ANIMATION_SPEED: f32 = 0.5;
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<NpcAnimations>();
@@ -69,6 +71,7 @@ pub(crate) enum NpcAnimationState {
     Airborne,
     Attack(f32),
     Walking(f32),
+    Climbing,
 }
 
 #[cfg_attr(feature = "hot_patch", hot)]
@@ -79,6 +82,7 @@ fn play_animations(
         &TnuaController,
         &AnimationPlayers,
         Option<&Attacking>,
+        Option<&UsingNavLink>,
     )>,
     mut q_animation: Query<(
         &NpcAnimations,
@@ -87,7 +91,8 @@ fn play_animations(
     )>,
     mut commands: Commands,
 ) {
-    for (entity, mut animating_state, controller, anim_players, attacking) in &mut query {
+    for (entity, mut animating_state, controller, anim_players, attacking, using_link) in &mut query
+    {
         let mut iter = q_animation.iter_many_mut(anim_players.iter());
         while let Some((animations, mut anim_player, mut transitions)) = iter.fetch_next() {
             match animating_state.update_by_discriminant({
@@ -100,6 +105,8 @@ fn play_animations(
                     } else {
                         NpcAnimationState::Attack(attacking.speed)
                     }
+                } else if using_link.is_some_and(UsingNavLink::is_climbing) {
+                    NpcAnimationState::Climbing
                 } else {
                     let Some((_, basis_state)) = controller.concrete_basis::<TnuaBuiltinWalk>()
                     else {
@@ -167,6 +174,17 @@ fn play_animations(
                             )
                             .repeat();
                     }
+                    // There is no climbing animation, so a slow walk has to do.
+                    NpcAnimationState::Climbing => {
+                        transitions
+                            .play(
+                                &mut anim_player,
+                                animations.walk,
+                                Duration::from_millis(200),
+                            )
+                            .set_speed(CLIMB_ANIMATION_SPEED)
+                            .repeat();
+                    }
                 },
             }
         }
@@ -1,3 +1,11 @@
+//! Melee attacks of the NPCs.
+//!
+//! Each archetype defines its [`MeleeAttack`] in its [`NpcStats`](super::stats::NpcStats). During the hit frames
+//! of the attack animation, a hitbox is spawned in front of the NPC that damages, knocks back and possibly
+//! staggers the player.
+
+use std::ops::Range;
+
 use avian3d::prelude::*;
 use bevy::{prelude::*, time::Stopwatch};
 #[cfg(feature = "hot_patch")]
@@ -5,12 +13,15 @@ use bevy_simple_subsecond_system::hot;
 
 use crate::{
     PrePhysicsAppSystems,
-    gameplay::{health::OnDamage, npc::stats::NpcStats, player::Player},
+    gameplay::{
+        health::{DamageKind, OnDamage},
+        player::{Player, movement::Staggered},
+    },
     third_party::avian3d::CollisionLayer,
 };
 
 pub(super) fn plugin(app: &mut App) {
-    app.register_type::<Attacking>();
+    app.register_type::<(Attacking, MeleeAttack)>();
     app.register_type::<AttackPhase>();
     app.register_type::<AttackStopwatch>();
     app.add_systems(
@@ -35,21 +46,21 @@ fn stop_attack(trigger: Trigger<OnRemove, Attacking>, mut commands: Commands) {
     commands
         .entity(entity)
         .try_remove::<(AttackPhase, AttackStopwatch)>();
+    commands.entity(entity).queue_handled(
+        |mut entity: EntityWorldMut| {
+            entity.despawn_related::<Hitbox>();
+        },
+        bevy::ecs::error::ignore,
+    );
 }
 
 #[cfg_attr(feature = "hot_patch", hot)]
 fn update_attack_phase(
-    mut query: Query<(
-        Entity,
-        &mut AttackPhase,
-        &Attacking,
-        &mut AttackStopwatch,
-        &NpcStats,
-    )>,
+    mut query: Query<(Entity, &mut AttackPhase, &Attacking, &mut AttackStopwatch)>,
     time: Res<Time>,
     mut commands: Commands,
 ) {
-    for (entity, mut phase, attacking, mut stopwatch, stats) in query.iter_mut() {
+    for (entity, mut phase, attacking, mut stopwatch) in query.iter_mut() {
         stopwatch.0.tick(time.delta());
         match *phase {
             AttackPhase::Windup => {
@@ -60,8 +71,12 @@ fn update_attack_phase(
                             HitboxOf(entity),
                             ChildOf(entity),
                             Sensor,
-                            Transform::from_xyz(0.0, 0.0, -1.5),
-                            Collider::cuboid(1.5 * stats.size, 1.5 * stats.size, 1.5 * stats.size),
+                            Transform::from_translation(attacking.attack.hitbox_offset),
+                            Collider::cuboid(
+                                attacking.attack.hitbox_size.x,
+                                attacking.attack.hitbox_size.y,
+                                attacking.attack.hitbox_size.z,
+                            ),
                             CollisionEventsEnabled,
                             CollisionLayers::new(CollisionLayer::Sensor, CollisionLayer::Player),
                         ))
@@ -87,22 +102,48 @@ fn update_attack_phase(
 #[cfg_attr(feature = "hot_patch", hot)]
 fn hit_player(
     trigger: Trigger<OnCollisionStart>,
-    player: Query<(), With<Player>>,
+    mut player: Query<(&mut LinearVelocity, &Transform), With<Player>>,
     name: Query<NameOrEntity>,
+    hitboxes: Query<(&HitboxOf, &GlobalTransform)>,
+    attackers: Query<(&Attacking, &Transform)>,
     mut commands: Commands,
 ) {
     let Some(body) = trigger.event().body else {
         error!("Enemy hit collision without body");
         return;
     };
-    if !player.contains(body) {
+    let Ok((mut velocity, player_transform)) = player.get_mut(body) else {
         let name = name.get(body).unwrap();
         error!("Enemy hit non-player: {name}");
         return;
+    };
+    let Ok((hitbox_of, hitbox_transform)) = hitboxes.get(trigger.target()) else {
+        return;
+    };
+    let Ok((attacking, attacker_transform)) = attackers.get(hitbox_of.0) else {
+        return;
+    };
+    commands.entity(body).trigger(
+        OnDamage::new(attacking.damage, DamageKind::Melee)
+            .with_instigator(hitbox_of.0)
+            .with_position(hitbox_transform.translation()),
+    );
+
+    let away = (player_transform.translation - attacker_transform.translation)
+        .with_y(0.0)
+        .normalize_or_zero();
+    // Lift the player a bit so that the knockback isn't eaten by the ground.
+    velocity.0 += (away + Vec3::Y * KNOCKBACK_LIFT) * attacking.attack.knockback;
+    if let Some(seconds) = attacking.attack.stagger_secs {
+        commands
+            .entity(body)
+            .insert(Staggered(Timer::from_seconds(seconds, TimerMode::Once)));
     }
-    commands.entity(body).trigger(OnDamage(10.0));
 }
 
+/// How much of the knockback of an attack goes upwards.
+const KNOCKBACK_LIFT: f32 = 0.3;
+
 #[derive(Component, Deref, DerefMut, Debug, Reflect)]
 #[reflect(Component)]
 #[relationship(relationship_target = Hitbox)]
@@ -119,6 +160,34 @@ pub(crate) struct Attacking {
     pub(crate) speed: f32,
     pub(crate) damage: f32,
     pub(crate) dir: Option<Dir3>,
+    pub(crate) attack: MeleeAttack,
+}
+
+/// How an NPC archetype attacks the player. The damage is [`NpcStats::attack_damage`](super::stats::NpcStats::attack_damage), as it is scaled with the waves.
+#[derive(Debug, Clone, Reflect)]
+pub(crate) struct MeleeAttack {
+    /// The full extents of the hitbox.
+    pub(crate) hitbox_size: Vec3,
+    /// Where the hitbox is relative to the NPC. The NPC faces towards negative Z.
+    pub(crate) hitbox_offset: Vec3,
+    /// The frames of the attack animation during which the hitbox is active, at 24 frames per second.
+    pub(crate) hit_frames: Range<u32>,
+    /// The speed the player is pushed away with when hit.
+    pub(crate) knockback: f32,
+    /// How long the player is staggered for when hit, if at all.
+    pub(crate) stagger_secs: Option<f32>,
+}
+
+impl Default for MeleeAttack {
+    fn default() -> Self {
+        Self {
+            hitbox_size: Vec3::splat(1.5),
+            hitbox_offset: Vec3::new(0.0, 0.0, -1.5),
+            hit_frames: 24..29,
+            knockback: 4.0,
+            stagger_secs: None,
+        }
+    }
 }
 
 #[derive(Component, Debug, Reflect, Default, Clone)]
@@ -136,11 +205,11 @@ enum AttackPhase {
 
 impl Attacking {
     pub(crate) fn attack_start_secs(&self) -> f32 {
-        self.frame_to_secs(24)
+        self.frame_to_secs(self.attack.hit_frames.start)
     }
 
     pub(crate) fn attack_end_secs(&self) -> f32 {
-        self.frame_to_secs(29)
+        self.frame_to_secs(self.attack.hit_frames.end)
     }
 
     fn frame_to_secs(&self, frame: u32) -> f32 {
@@ -0,0 +1,229 @@
+//! Utility AI deciding what NPCs do next.
+//!
+//! Every [`Action`] scores how useful it would be right now, from 0 to 1, based on a [`Context`] describing the
+//! NPC's situation. The score is multiplied by how much the NPC's archetype favours the action, as configured by
+//! its [`Behavior`], and the best action wins. Actions that are not part of an archetype's behavior are never
+//! taken, so new archetypes are put together by listing the actions they should have.
+//!
+//! Actions that take a while, like attacking or being staggered, are not reconsidered until they are done.
+
+use bevy::prelude::*;
+
+use crate::gameplay::npc::{ai_state::AiState, perception::Perception, stats::NpcStats};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(Action, Behavior, Decision)>();
+}
+
+/// Something an NPC can decide to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub(crate) enum Action {
+    /// Stand around.
+    #[default]
+    Idle,
+    /// Stroll to a random point nearby.
+    Wander,
+    /// Go to where the player was last seen or heard.
+    Investigate,
+    /// React to noticing the player.
+    Alert,
+    /// Run towards the player.
+    Chase,
+    /// Hit the player in melee.
+    Melee,
+    /// Aim and spit at the player.
+    Spit,
+    /// Back away from a player that got too close.
+    Reposition,
+}
+
+/// The actions an NPC archetype can take and how strongly it favours each of them.
+#[derive(Debug, Clone, Reflect)]
+pub(crate) struct Behavior(Vec<(Action, f32)>);
+
+impl Behavior {
+    /// Shambles around until it notices the player, then chases and hits them.
+    pub(crate) fn melee() -> Self {
+        Self(vec![
+            (Action::Idle, 1.0),
+            (Action::Wander, 1.0),
+            (Action::Investigate, 1.0),
+            (Action::Alert, 1.0),
+            (Action::Chase, 1.0),
+            (Action::Melee, 1.0),
+        ])
+    }
+
+    /// Keeps its distance from the player and spits at them.
+    pub(crate) fn ranged() -> Self {
+        Self(vec![
+            (Action::Idle, 1.0),
+            (Action::Wander, 1.0),
+            (Action::Investigate, 1.0),
+            (Action::Alert, 1.0),
+            (Action::Chase, 1.0),
+            (Action::Spit, 1.0),
+            (Action::Reposition, 1.0),
+        ])
+    }
+
+    /// Changes how strongly the archetype favours an action, adding it if it was missing.
+    pub(crate) fn with_weight(mut self, action: Action, weight: f32) -> Self {
+        match self.0.iter_mut().find(|(other, _)| *other == action) {
+            Some((_, existing)) => *existing = weight,
+            None => self.0.push((action, weight)),
+        }
+        self
+    }
+
+    /// The most useful action in the given context, along with its weighted score.
+    pub(crate) fn choose(&self, context: &Context) -> Option<(Action, f32)> {
+        self.0
+            .iter()
+            .map(|&(action, weight)| (action, action.score(context) * weight))
+            .filter(|(_, score)| *score > 0.0)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+    }
+}
+
+impl Default for Behavior {
+    fn default() -> Self {
+        Self::melee()
+    }
+}
+
+/// What an NPC knows about its situation when deciding what to do.
+pub(crate) struct Context<'a> {
+    pub(crate) state: &'a AiState,
+    pub(crate) perception: &'a Perception,
+    pub(crate) stats: &'a NpcStats,
+    pub(crate) distance_to_player: f32,
+    /// Whether nothing blocks the way between the NPC and the player, regardless of where the NPC looks.
+    /// Only checked for archetypes with a ranged attack.
+    pub(crate) player_in_sight: bool,
+    /// Whether the NPC got close enough to the point it is chasing, wandering to or investigating.
+    pub(crate) reached_target: bool,
+    /// Whether the target of the navigation cannot be reached.
+    pub(crate) target_unreachable: bool,
+    pub(crate) can_spit: bool,
+}
+
+impl Action {
+    /// How useful the action would be in the given context, from 0 to 1.
+    fn score(self, context: &Context) -> f32 {
+        let unaware = context.state.is_unaware();
+        let tracking = !context.perception.lost_track();
+        let hunting = tracking && !unaware;
+        match self {
+            // The fallback for when nothing else is worth doing.
+            Action::Idle => match context.state {
+                AiState::Idle(timer) if !timer.finished() => 0.3,
+                _ => 0.1,
+            },
+            Action::Wander => match context.state {
+                AiState::Idle(timer) if timer.finished() => 0.2,
+                AiState::Wander(_) if !context.reached_target && !context.target_unreachable => 0.2,
+                _ => 0.0,
+            },
+            Action::Investigate => {
+                let investigating = matches!(context.state, AiState::Investigate(_));
+                let done = investigating && (context.reached_target || context.target_unreachable);
+                let has_lead = investigating || context.perception.last_known_position().is_some();
+                if !tracking && !done && has_lead {
+                    0.5
+                } else {
+                    0.0
+                }
+            }
+            Action::Alert => {
+                if unaware && tracking {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Action::Chase => {
+                if hunting {
+                    0.5
+                } else {
+                    0.0
+                }
+            }
+            Action::Melee => {
+                if hunting && context.reached_target {
+                    0.8
+                } else {
+                    0.0
+                }
+            }
+            Action::Spit => {
+                if hunting && context.reached_target && context.can_spit && context.player_in_sight
+                {
+                    0.8
+                } else {
+                    0.0
+                }
+            }
+            Action::Reposition => match &context.stats.ranged {
+                Some(ranged) if hunting && context.distance_to_player < ranged.min_distance => 0.9,
+                _ => 0.0,
+            },
+        }
+    }
+
+    /// The action the NPC is currently taking, if it is one that can be reconsidered.
+    pub(crate) fn ongoing(state: &AiState) -> Option<Self> {
+        match state {
+            AiState::Idle(_) => Some(Action::Idle),
+            AiState::Wander(_) => Some(Action::Wander),
+            AiState::Investigate(_) => Some(Action::Investigate),
+            AiState::Chase => Some(Action::Chase),
+            _ => None,
+        }
+    }
+}
+
+/// The last action an NPC decided on. Used by the debug gizmos.
+#[derive(Component, Debug, Clone, Copy, Default, Reflect)]
+#[reflect(Component)]
+pub(crate) struct Decision {
+    pub(crate) action: Action,
+    pub(crate) score: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn heavier_weight_wins() {
+        let stats = NpcStats::default();
+        let perception = Perception::default();
+        let mut timer = Timer::default();
+        timer.tick(Duration::ZERO);
+        let state = AiState::Idle(timer);
+        let context = Context {
+            state: &state,
+            perception: &perception,
+            stats: &stats,
+            distance_to_player: 50.0,
+            player_in_sight: false,
+            reached_target: false,
+            target_unreachable: false,
+            can_spit: false,
+        };
+        // A finished idle timer makes wandering more useful than idling.
+        assert_eq!(
+            Behavior::melee().choose(&context).map(|(action, _)| action),
+            Some(Action::Wander)
+        );
+        // Unless the archetype is lazy.
+        let lazy = Behavior::melee().with_weight(Action::Idle, 5.0);
+        assert_eq!(
+            lazy.choose(&context).map(|(action, _)| action),
+            Some(Action::Idle)
+        );
+    }
+}
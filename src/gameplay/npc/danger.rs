@@ -0,0 +1,209 @@
+//! Blast zones that careful NPCs walk around.
+//!
+//! Every explosive prop resting in the level that hits hard enough gets a [`DangerZoneOf`] covering its blast radius. The zone
+//! is a flat navmesh affector lying on the floor below the explosive, whose surface is marked with [`DANGER_TYPE_INDEX`],
+//! so the navmesh around the explosive is rebuilt with that type. The navmesh treats the zone as floor, so explosives
+//! lose their zone while they are held or moving and get a new one once they come to rest.
+//! Walking through it costs as much as the rest of the navmesh by default, so most NPCs happily shamble into a barrel
+//! cluster. Archetypes with a [`NpcStats::danger_cost`] above 1 find it more expensive and take the long way around.
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+#[cfg(feature = "hot_patch")]
+use bevy_simple_subsecond_system::hot;
+use oxidized_navigation::{Area, NavMeshAffector, NavMeshAreaType};
+
+use crate::{
+    auto_timer::AutoTimer,
+    gameplay::{
+        explosion::{ExplodeOnShoot, Exploded, Explosive},
+        npc::{Npc, stats::NpcStats},
+        player::{grenades::Grenade, pickup::HeldProp},
+    },
+    screens::Screen,
+    third_party::{avian3d::CollisionLayer, bevy_landmass::DANGER_TYPE_INDEX},
+};
+
+/// Explosives dealing less damage than this are not worth walking around.
+const DANGER_DAMAGE_THRESHOLD: f32 = 50.0;
+/// Explosives moving slower than this are considered to be resting.
+const RESTING_SPEED: f32 = 0.1;
+/// How far above its origin an explosive looks for the floor below it.
+const FLOOR_SEARCH_HEIGHT: f32 = 0.5;
+/// Explosives further above the floor than this do not get a zone.
+const MAX_FLOOR_DISTANCE: f32 = 2.0;
+/// Thick enough to be picked up by the navmesh, thin enough to be stepped onto.
+const DANGER_ZONE_HEIGHT: f32 = 0.1;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(DangerZoneOf, DangerZone)>();
+    // Runs after transform propagation, so that the zones of new explosives start out in the right place.
+    app.add_systems(
+        PostUpdate,
+        (remove_moving_danger_zones, add_danger_zones)
+            .chain()
+            .after(TransformSystem::TransformPropagate)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_observer(remove_danger_zone);
+}
+
+/// The area around an explosive that NPCs avoid.
+#[derive(Component, Deref, DerefMut, Debug, Reflect)]
+#[reflect(Component)]
+#[relationship(relationship_target = DangerZone)]
+pub(crate) struct DangerZoneOf(Entity);
+
+#[derive(Component, Deref, DerefMut, Debug, Reflect)]
+#[reflect(Component)]
+#[relationship_target(relationship = DangerZoneOf, linked_spawn)]
+pub(crate) struct DangerZone(Entity);
+
+/// Explosive props that are armed and waiting to be shot.
+/// Grenades and burning fuses go off before the navmesh around them could be rebuilt.
+type ArmedExplosive = (
+    With<ExplodeOnShoot>,
+    Without<Grenade>,
+    Without<AutoTimer>,
+    Without<Exploded>,
+    // NPCs blow up when they die, but they walk in crowds and would only get in each other's way.
+    Without<Npc>,
+);
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn add_danger_zones(
+    explosives: Query<
+        (
+            Entity,
+            &Explosive,
+            &GlobalTransform,
+            Option<&LinearVelocity>,
+        ),
+        (ArmedExplosive, Without<DangerZone>),
+    >,
+    held_props: Query<&HeldProp>,
+    spatial_query: SpatialQuery,
+    mut commands: Commands,
+) {
+    let filter = SpatialQueryFilter::from_mask(CollisionLayer::Default);
+    for (entity, explosive, transform, velocity) in &explosives {
+        if explosive.damage < DANGER_DAMAGE_THRESHOLD || is_moving(entity, velocity, &held_props) {
+            continue;
+        }
+        let origin = transform.translation() + Vec3::Y * FLOOR_SEARCH_HEIGHT;
+        let Some(hit) = spatial_query.cast_ray(
+            origin,
+            Dir3::NEG_Y,
+            FLOOR_SEARCH_HEIGHT + MAX_FLOOR_DISTANCE,
+            true,
+            &filter,
+        ) else {
+            continue;
+        };
+        let floor = origin - Vec3::Y * hit.distance;
+        commands.spawn((
+            Name::new("Danger Zone"),
+            DangerZoneOf(entity),
+            Transform::from_translation(floor + Vec3::Y * DANGER_ZONE_HEIGHT / 2.0),
+            Collider::cylinder(explosive.radius, DANGER_ZONE_HEIGHT),
+            // Only there for the navmesh, so nothing should touch it.
+            Sensor,
+            CollisionLayers::NONE,
+            NavMeshAffector,
+            NavMeshAreaType(Some(Area(DANGER_TYPE_INDEX as u16))),
+            StateScoped(Screen::Gameplay),
+        ));
+    }
+}
+
+/// Removes the zones of explosives that are being moved.
+/// They get a new one once they come to rest, rather than dragging a patch of floor along with them.
+fn remove_moving_danger_zones(
+    explosives: Query<(Entity, Option<&LinearVelocity>), With<DangerZone>>,
+    held_props: Query<&HeldProp>,
+    mut commands: Commands,
+) {
+    for (entity, velocity) in &explosives {
+        if is_moving(entity, velocity, &held_props) {
+            commands.entity(entity).despawn_related::<DangerZone>();
+        }
+    }
+}
+
+fn is_moving(
+    entity: Entity,
+    velocity: Option<&LinearVelocity>,
+    held_props: &Query<&HeldProp>,
+) -> bool {
+    velocity.is_some_and(|velocity| velocity.length() > RESTING_SPEED)
+        || held_props.iter().any(|held| held.prop() == entity)
+}
+
+/// An explosive that already went off is no longer a danger.
+fn remove_danger_zone(
+    trigger: Trigger<OnAdd, Exploded>,
+    zones: Query<(), With<DangerZone>>,
+    mut commands: Commands,
+) {
+    if zones.contains(trigger.target()) {
+        commands
+            .entity(trigger.target())
+            .despawn_related::<DangerZone>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headless::{headless_app, run_updates};
+
+    fn spawn_explosive_above_floor(app: &mut App) -> Entity {
+        app.add_plugins(super::plugin);
+        // The top of the floor is at a height of 2.
+        app.world_mut().spawn((
+            RigidBody::Static,
+            Collider::cuboid(20.0, 1.0, 20.0),
+            Transform::from_xyz(0.0, 1.5, 0.0),
+        ));
+        let explosive = app
+            .world_mut()
+            .spawn((
+                Explosive::default(),
+                ExplodeOnShoot,
+                Transform::from_xyz(3.0, 3.0, -1.0),
+            ))
+            .id();
+        run_updates(app, 3);
+        explosive
+    }
+
+    #[test]
+    fn zone_lies_on_the_floor_below_the_explosive() {
+        let mut app = headless_app();
+        let explosive = spawn_explosive_above_floor(&mut app);
+
+        let zone = app
+            .world()
+            .get::<DangerZone>(explosive)
+            .expect("The explosive has no danger zone");
+        let translation = app.world().get::<Transform>(zone.0).unwrap().translation;
+        let expected = Vec3::new(3.0, 2.0 + DANGER_ZONE_HEIGHT / 2.0, -1.0);
+        assert!(
+            translation.distance(expected) < 1e-3,
+            "The zone is at {translation}, expected {expected}"
+        );
+    }
+
+    #[test]
+    fn moving_explosive_loses_its_zone() {
+        let mut app = headless_app();
+        let explosive = spawn_explosive_above_floor(&mut app);
+        assert!(app.world().get::<DangerZone>(explosive).is_some());
+
+        app.world_mut()
+            .entity_mut(explosive)
+            .insert(LinearVelocity(Vec3::X * 2.0));
+        run_updates(&mut app, 1);
+        assert!(app.world().get::<DangerZone>(explosive).is_none());
+    }
+}
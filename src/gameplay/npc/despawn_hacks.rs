@@ -3,7 +3,7 @@ use bevy::prelude::*;
 use crate::{
     PostPhysicsAppSystems,
     gameplay::{
-        health::OnDamage,
+        health::{DamageKind, OnDamage},
         npc::{Npc, ai_state::AiState},
         player::Player,
     },
@@ -71,7 +71,9 @@ fn despawn_lazy(
             }
             lazy.timer.tick(time.delta());
             if lazy.timer.finished() {
-                commands.entity(entity).trigger(OnDamage(1000.0));
+                commands
+                    .entity(entity)
+                    .trigger(OnDamage::new(1000.0, DamageKind::Despawn).with_position(translation));
             }
             continue;
         }
@@ -116,7 +118,9 @@ fn despawn_lonely(
     if let Ok((_entity, mut lonely)) = lonelies.get_mut(lonely_entity) {
         lonely.0.tick(time.delta());
         if lonely.0.finished() {
-            commands.entity(lonely_entity).trigger(OnDamage(1000.0));
+            commands
+                .entity(lonely_entity)
+                .trigger(OnDamage::new(1000.0, DamageKind::Despawn));
         }
     } else {
         commands.entity(lonely_entity).insert(Lonely::default());
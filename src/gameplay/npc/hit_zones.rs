@@ -0,0 +1,152 @@
+//! Hit zones on the skeleton of the NPCs.
+//!
+//! Every NPC gets colliders attached to the bones of its skeleton, so that shots can tell a headshot from a hit
+//! in the leg. These colliders are only there for ray casts: they are in their own [`CollisionLayer::HitZone`],
+//! do not collide with anything and have no mass. How much damage each zone takes is defined per archetype
+//! in [`NpcStats::hit_zones`](super::stats::NpcStats::hit_zones). Shots that hit the capsule of an NPC
+//! without a hit zone behind it count as hits to the torso.
+
+use avian3d::prelude::*;
+use bevy::{prelude::*, scene::SceneInstanceReady};
+
+use crate::third_party::avian3d::CollisionLayer;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(HitZone, LastHit)>();
+}
+
+/// A part of an NPC's body that can be hit by a shot.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub(crate) enum HitZone {
+    Head,
+    Torso,
+    Arm,
+    Leg,
+}
+
+/// The damage multiplier of each [`HitZone`].
+#[derive(Debug, Clone, Copy, Reflect)]
+pub(crate) struct HitZoneMultipliers {
+    pub(crate) head: f32,
+    pub(crate) torso: f32,
+    pub(crate) arm: f32,
+    pub(crate) leg: f32,
+}
+
+impl HitZoneMultipliers {
+    pub(crate) fn get(&self, zone: HitZone) -> f32 {
+        match zone {
+            HitZone::Head => self.head,
+            HitZone::Torso => self.torso,
+            HitZone::Arm => self.arm,
+            HitZone::Leg => self.leg,
+        }
+    }
+}
+
+impl Default for HitZoneMultipliers {
+    fn default() -> Self {
+        Self {
+            head: 2.5,
+            torso: 1.0,
+            arm: 0.7,
+            leg: 0.6,
+        }
+    }
+}
+
+/// Where an NPC was last hit by a shot.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub(crate) struct LastHit {
+    pub(crate) zone: HitZone,
+    /// The point that was hit, relative to the NPC's position.
+    pub(crate) offset: Vec3,
+}
+
+/// A capsule attached to a bone, pointing along the bone towards its child.
+struct BoneCollider {
+    bone: &'static str,
+    zone: HitZone,
+    radius: f32,
+    length: f32,
+    /// How far along the bone the center of the capsule is.
+    offset: f32,
+}
+
+impl BoneCollider {
+    const fn new(bone: &'static str, zone: HitZone, radius: f32, length: f32) -> Self {
+        Self {
+            bone,
+            zone,
+            radius,
+            length,
+            offset: radius + length / 2.0,
+        }
+    }
+}
+
+/// The colliders of the zombie skeleton, in meters.
+const BONE_COLLIDERS: &[BoneCollider] = &[
+    BoneCollider::new("Head", HitZone::Head, 0.12, 0.0),
+    BoneCollider::new("Spine", HitZone::Torso, 0.16, 0.0),
+    BoneCollider::new("Spine2", HitZone::Torso, 0.17, 0.05),
+    BoneCollider::new("Hips", HitZone::Torso, 0.16, 0.0),
+    BoneCollider::new("LeftArm", HitZone::Arm, 0.06, 0.2),
+    BoneCollider::new("LeftForeArm", HitZone::Arm, 0.05, 0.22),
+    BoneCollider::new("RightArm", HitZone::Arm, 0.06, 0.2),
+    BoneCollider::new("RightForeArm", HitZone::Arm, 0.05, 0.22),
+    BoneCollider::new("LeftUpLeg", HitZone::Leg, 0.08, 0.32),
+    BoneCollider::new("LeftLeg", HitZone::Leg, 0.06, 0.32),
+    BoneCollider::new("RightUpLeg", HitZone::Leg, 0.08, 0.32),
+    BoneCollider::new("RightLeg", HitZone::Leg, 0.06, 0.32),
+];
+
+/// Attaches the hit zone colliders to the bones of an NPC model once its scene is spawned.
+pub(super) fn add_hit_zones(
+    trigger: Trigger<SceneInstanceReady>,
+    children: Query<&Children>,
+    parents: Query<&ChildOf>,
+    transforms: Query<&Transform>,
+    names: Query<&Name>,
+    mut commands: Commands,
+) {
+    let model = trigger.target();
+    let mut added = 0;
+    for entity in children.iter_descendants(model) {
+        let Ok(name) = names.get(entity) else {
+            continue;
+        };
+        // The glTF node names look like `mixamorig:LeftArm_25`.
+        let Some(bone) = name.as_str().strip_prefix("mixamorig:") else {
+            continue;
+        };
+        let bone = bone.rsplit_once('_').map_or(bone, |(bone, _index)| bone);
+        let Some(collider) = BONE_COLLIDERS.iter().find(|collider| collider.bone == bone) else {
+            continue;
+        };
+        // The skeleton is usually authored in different units than the model and scaled to fit.
+        // Undo that scale so that the collider can be defined in meters.
+        let skeleton_scale = std::iter::once(entity)
+            .chain(parents.iter_ancestors(entity))
+            .take_while(|&ancestor| ancestor != model)
+            .filter_map(|ancestor| transforms.get(ancestor).ok())
+            .fold(Vec3::ONE, |scale, transform| scale * transform.scale);
+        commands.entity(entity).with_child((
+            Name::new(format!("{bone} Hit Zone")),
+            collider.zone,
+            Transform::from_translation(Vec3::Y * collider.offset / skeleton_scale)
+                .with_scale(skeleton_scale.recip()),
+            Collider::capsule(collider.radius, collider.length),
+            ColliderDensity(0.0),
+            CollisionLayers::new(CollisionLayer::HitZone, LayerMask::NONE),
+        ));
+        added += 1;
+    }
+    if added == 0 {
+        warn!(
+            "NPC model {model} has no bones we know hit zones for. Shots will only hit its capsule."
+        );
+    }
+}
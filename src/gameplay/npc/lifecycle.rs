@@ -14,10 +14,17 @@ use crate::{
     audio::SoundEffect,
     despawn_after::{Despawn, DespawnAfter},
     gameplay::{
+        combo::ChainLink,
         explosion::{ExplodeOnDeath, OnExplode},
         gore_settings::{Gore, GoreSettings},
         health::{OnDamage, OnDeath},
-        npc::{ai_state::AiState, assets::NpcAssets, stats::NpcStats},
+        npc::{
+            ai_state::AiState,
+            assets::NpcAssets,
+            hit_zones::{HitZone, LastHit},
+            stats::NpcStats,
+        },
+        rng::{GameplayRng, RngStream},
     },
     screens::{Screen, loading::LoadingScreen},
     third_party::avian3d::CollisionLayer,
@@ -36,17 +43,32 @@ pub struct Gib;
 #[cfg_attr(feature = "hot_patch", hot)]
 fn on_enemy_death(
     trigger: Trigger<OnDeath>,
-    enemies: Query<(&Transform, &NpcStats, Has<ExplodeOnDeath>)>,
+    enemies: Query<(
+        &Transform,
+        &NpcStats,
+        Has<ExplodeOnDeath>,
+        Option<&ChainLink>,
+        Option<&LastHit>,
+    )>,
     npc_assets: Res<NpcAssets>,
     gore_settings: Res<GoreSettings>,
+    mut rng: ResMut<GameplayRng>,
     mut commands: Commands,
 ) {
     let entity = trigger.target();
-    let Ok((transform, stats, explode_on_death)) = enemies.get(entity) else {
+    let Ok((transform, stats, explode_on_death, chain, last_hit)) = enemies.get(entity) else {
         return;
     };
     if gore_settings.gibs != Gore::None {
-        let mut rng = rand::thread_rng();
+        let rng = rng.stream(RngStream::Gore);
+        // The limb that took the last shot is torn off first and shows up more often.
+        let hit_gibs = match last_hit.map(|hit| hit.zone) {
+            Some(HitZone::Head) => vec![&npc_assets.gib_head],
+            Some(HitZone::Torso) => vec![&npc_assets.gib_torso, &npc_assets.gib_pelvis],
+            Some(HitZone::Arm) => vec![&npc_assets.gib_arm_1, &npc_assets.gib_arm_2],
+            Some(HitZone::Leg) => vec![&npc_assets.gib_leg, &npc_assets.gib_foot],
+            None => Vec::new(),
+        };
         let mut gibs = ShuffleBag::try_new(
             [
                 &npc_assets.gib_head,
@@ -60,16 +82,21 @@ fn on_enemy_death(
                 &npc_assets.gib_foot,
                 &npc_assets.gib_foot,
                 &npc_assets.gib_pelvis,
-            ],
-            &mut rng,
+            ]
+            .into_iter()
+            .chain(hit_gibs.iter().chain(&hit_gibs).copied())
+            .collect::<Vec<_>>(),
+            rng,
         )
         .unwrap();
 
-        for _ in 0..gore_settings.gib_count {
-            let gib = *gibs.pick(&mut rng);
+        for index in 0..gore_settings.gib_count {
             let offset_radius = 0.5;
-            let offset = Sphere::new(offset_radius).sample_interior(&mut rng);
-            let position = transform.translation + offset;
+            let offset = Sphere::new(offset_radius).sample_interior(rng);
+            let (gib, position) = match (last_hit, hit_gibs.first()) {
+                (Some(hit), Some(&gib)) if index == 0 => (gib, transform.translation + hit.offset),
+                _ => (*gibs.pick(rng), transform.translation + offset),
+            };
 
             let mut entity_commands = commands.spawn((
                 Gib,
@@ -102,7 +129,9 @@ fn on_enemy_death(
     );
 
     if explode_on_death {
-        commands.entity(entity).trigger(OnExplode);
+        commands.entity(entity).trigger(OnExplode {
+            chain: chain.copied(),
+        });
     }
 }
 
@@ -136,6 +165,7 @@ fn grunt_passively(
     mut commands: Commands,
     time: Res<Time>,
     mut npc_assets: ResMut<NpcAssets>,
+    mut rng: ResMut<GameplayRng>,
 ) {
     for (ai_state, stats, transform, entity) in enemy.iter_mut() {
         if !matches!(*ai_state, AiState::Chase) {
@@ -144,7 +174,7 @@ fn grunt_passively(
 
         let grunt_chance_per_second = 0.3;
         let grunt_chance = grunt_chance_per_second * time.delta_secs();
-        if !rand::thread_rng().gen_bool(grunt_chance as f64) {
+        if !rng.stream(RngStream::Npc).gen_bool(grunt_chance as f64) {
             continue;
         }
 
@@ -162,6 +192,7 @@ fn stagger_on_hit(
     mut enemies: Query<(&mut AiState, &NpcStats, &Transform)>,
     mut commands: Commands,
     mut npc_assets: ResMut<NpcAssets>,
+    mut rng: ResMut<GameplayRng>,
     state: Res<State<Screen>>,
 ) {
     if *state != Screen::Gameplay {
@@ -176,8 +207,9 @@ fn stagger_on_hit(
         return;
     }
 
-    if rand::thread_rng().gen_bool(stats.stagger_chance as f64) {
-        let duration = rand::thread_rng().gen_range(stats.stagger_duration.clone());
+    let rng = rng.stream(RngStream::Npc);
+    if rng.gen_bool(stats.stagger_chance as f64) {
+        let duration = rng.gen_range(stats.stagger_duration.clone());
         *ai_state = AiState::Stagger(Timer::from_seconds(duration, TimerMode::Once));
         let handle = npc_assets
             .stagger_sound
@@ -0,0 +1,415 @@
+//! Off-mesh links that let NPCs jump down ledges, vault over cover and climb ladders.
+//!
+//! Level designers place a [`NavLinkStart`] and a [`NavLinkEnd`] with the same `link` name in TrenchBroom.
+//! The landmass version we use has no notion of links between disconnected parts of the navmesh, so we route
+//! over them ourselves: when an NPC has no path to its target, it heads for the start of the link that gets it
+//! closest, then crosses it by jumping or climbing, and lets landmass take over again from the end of the link.
+//! As links are only considered when there is no path at all, NPCs never take them as a shortcut.
+
+use std::str::FromStr;
+
+use anyhow::bail;
+use avian3d::prelude::*;
+use bevy::{platform::collections::HashSet, prelude::*};
+use bevy_landmass::{AgentState, prelude::*};
+#[cfg(feature = "hot_patch")]
+use bevy_simple_subsecond_system::hot;
+use bevy_tnua::TnuaToggle;
+use bevy_trenchbroom::prelude::*;
+
+use crate::{
+    PrePhysicsAppSystems,
+    gameplay::npc::{
+        ai_state::AiState,
+        navigation::{AgentOf, update_agent_target},
+        stats::NpcStats,
+    },
+};
+
+/// How close an NPC has to get to the start of a link to cross it.
+const LINK_ENTER_DISTANCE: f32 = 0.8;
+/// How close an NPC has to get to the end of a link for the crossing to be done.
+const LINK_EXIT_DISTANCE: f32 = 0.6;
+/// An NPC that could not reach or cross a link in this time gives up on it.
+const LINK_TIMEOUT_SECS: f32 = 8.0;
+/// How long an NPC that gave up on a link waits before trying links again.
+const LINK_COOLDOWN_SECS: f32 = 3.0;
+/// How far above the end of a jump or vault the NPC jumps.
+const JUMP_CLEARANCE: f32 = 0.6;
+/// How fast NPCs climb ladders.
+const CLIMB_SPEED: f32 = 2.5;
+/// How far above the top of a ladder the feet of an NPC get before it steps off.
+const CLIMB_CLEARANCE: f32 = 0.3;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(NavLinkStart, NavLinkEnd, NavLink)>();
+    app.register_type::<(UsingNavLink, NavLinkCooldown)>();
+    app.add_systems(Update, resolve_nav_links);
+    app.add_systems(
+        RunFixedMainLoop,
+        target_nav_links
+            .after(update_agent_target)
+            .in_set(PrePhysicsAppSystems::UpdateNavmeshTargets),
+    );
+    app.add_systems(Update, tick_nav_link_cooldowns);
+    app.add_observer(stop_climbing);
+}
+
+/// Where NPCs enter an off-mesh link, placed on the floor. Place a [`NavLinkEnd`] with the same `link` where they
+/// should come out.
+#[derive(PointClass, Component, Debug, Reflect)]
+#[reflect(QuakeClass, Component)]
+#[base(Transform)]
+pub(crate) struct NavLinkStart {
+    /// The name shared with the [`NavLinkEnd`].
+    link: String,
+    /// How NPCs cross the link: `jump`, `drop`, `vault` or `climb`.
+    kind: String,
+}
+
+impl Default for NavLinkStart {
+    fn default() -> Self {
+        Self {
+            link: String::new(),
+            kind: "jump".to_string(),
+        }
+    }
+}
+
+/// Where NPCs come out of an off-mesh link.
+#[derive(PointClass, Component, Debug, Default, Reflect)]
+#[reflect(QuakeClass, Component)]
+#[base(Transform)]
+pub(crate) struct NavLinkEnd {
+    /// The name shared with the [`NavLinkStart`].
+    link: String,
+}
+
+/// How NPCs cross a [`NavLink`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub(crate) enum NavLinkKind {
+    /// Jump up onto a ledge or across a gap.
+    Jump,
+    /// Walk off a ledge.
+    Drop,
+    /// Hop over something in the way.
+    Vault,
+    /// Climb straight up, then step off at the top.
+    Climb,
+}
+
+impl FromStr for NavLinkKind {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> anyhow::Result<Self> {
+        match text.trim() {
+            "jump" => Ok(Self::Jump),
+            "drop" => Ok(Self::Drop),
+            "vault" => Ok(Self::Vault),
+            "climb" => Ok(Self::Climb),
+            other => {
+                bail!("\"{other}\" is not a link kind, expected `jump`, `drop`, `vault` or `climb`")
+            }
+        }
+    }
+}
+
+/// A [`NavLinkStart`] whose [`NavLinkEnd`] was found.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub(crate) struct NavLink {
+    pub(crate) start: Vec3,
+    pub(crate) end: Vec3,
+    pub(crate) kind: NavLinkKind,
+}
+
+impl NavLink {
+    /// How high to jump to cross the link, if it is crossed by jumping.
+    pub(crate) fn jump_height(&self) -> Option<f32> {
+        match self.kind {
+            NavLinkKind::Jump | NavLinkKind::Vault => {
+                Some((self.end.y - self.start.y).max(0.0) + JUMP_CLEARANCE)
+            }
+            NavLinkKind::Drop | NavLinkKind::Climb => None,
+        }
+    }
+}
+
+/// An NPC taking an off-mesh link.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub(crate) struct UsingNavLink {
+    pub(crate) link: NavLink,
+    /// Whether the NPC reached the start and is now crossing the link.
+    pub(crate) crossing: bool,
+    /// Whether the NPC already took off, for links that are crossed by jumping.
+    pub(crate) jumped: bool,
+    timeout: Timer,
+}
+
+impl UsingNavLink {
+    pub(crate) fn is_climbing(&self) -> bool {
+        self.crossing && self.link.kind == NavLinkKind::Climb
+    }
+}
+
+/// The NPC gave up on a link recently and does not look for others until the timer finishes.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+struct NavLinkCooldown(Timer);
+
+fn resolve_nav_links(
+    starts: Query<(Entity, &NavLinkStart, &GlobalTransform), Without<NavLink>>,
+    ends: Query<(&NavLinkEnd, &GlobalTransform)>,
+    mut reported: Local<HashSet<Entity>>,
+    mut commands: Commands,
+) {
+    for (entity, start, start_transform) in &starts {
+        let Some((_, end_transform)) = ends.iter().find(|(end, _)| end.link == start.link) else {
+            if reported.insert(entity) {
+                error!(
+                    "Link \"{}\" has a start but no end, so NPCs cannot take it",
+                    start.link
+                );
+            }
+            continue;
+        };
+        let kind = start.kind.parse().unwrap_or_else(|err| {
+            error!(
+                "Link \"{}\" has an invalid `kind`, jumping instead: {err:#}",
+                start.link
+            );
+            NavLinkKind::Jump
+        });
+        commands.entity(entity).insert(NavLink {
+            start: start_transform.translation(),
+            end: end_transform.translation(),
+            kind,
+        });
+    }
+}
+
+/// Sends NPCs without a path to the most promising link, and through it.
+#[cfg_attr(feature = "hot_patch", hot)]
+fn target_nav_links(
+    mut agents: Query<(&AgentOf, &AgentState, &mut AgentTarget3d)>,
+    mut npcs: Query<(
+        Entity,
+        &Transform,
+        &AiState,
+        &NpcStats,
+        Option<&mut UsingNavLink>,
+        Has<NavLinkCooldown>,
+    )>,
+    links: Query<&NavLink>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (agent_of, agent_state, mut target) in &mut agents {
+        let Ok((npc, transform, ai_state, stats, using_link, cooldown)) = npcs.get_mut(**agent_of)
+        else {
+            continue;
+        };
+        let position = transform.translation;
+        if let Some(mut using_link) = using_link {
+            let link = using_link.link;
+            let stuck = using_link.timeout.tick(time.delta()).finished()
+                || (!using_link.crossing && matches!(agent_state, AgentState::NoPath));
+            if stuck {
+                commands
+                    .entity(npc)
+                    .remove::<UsingNavLink>()
+                    .insert(NavLinkCooldown(Timer::from_seconds(
+                        LINK_COOLDOWN_SECS,
+                        TimerMode::Once,
+                    )));
+                continue;
+            }
+            if using_link.crossing {
+                let feet = position.y - stats.float_height();
+                if position.xz().distance(link.end.xz()) < LINK_EXIT_DISTANCE
+                    && (feet - link.end.y).abs() < 1.0
+                {
+                    commands.entity(npc).remove::<UsingNavLink>();
+                }
+            } else if position.xz().distance(link.start.xz()) < LINK_ENTER_DISTANCE {
+                using_link.crossing = true;
+                if link.kind == NavLinkKind::Climb {
+                    commands
+                        .entity(npc)
+                        .insert((TnuaToggle::Disabled, GravityScale(0.0)));
+                }
+            } else {
+                *target = AgentTarget3d::Point(link.start);
+            }
+            continue;
+        }
+
+        let wants_to_move = matches!(
+            ai_state,
+            AiState::Chase | AiState::Wander(..) | AiState::Investigate(..)
+        );
+        if cooldown || !wants_to_move || !matches!(agent_state, AgentState::NoPath) {
+            continue;
+        }
+        let AgentTarget3d::Point(destination) = *target else {
+            continue;
+        };
+        // Take the link that leads closest to the destination, as long as it gets the NPC closer at all.
+        let Some(link) = links
+            .iter()
+            .filter(|link| link.end.distance(destination) < position.distance(destination))
+            .min_by(|a, b| {
+                let cost =
+                    |link: &NavLink| position.distance(link.start) + link.end.distance(destination);
+                cost(a).total_cmp(&cost(b))
+            })
+        else {
+            continue;
+        };
+        *target = AgentTarget3d::Point(link.start);
+        commands.entity(npc).insert(UsingNavLink {
+            link: *link,
+            crossing: false,
+            jumped: false,
+            timeout: Timer::from_seconds(LINK_TIMEOUT_SECS, TimerMode::Once),
+        });
+    }
+}
+
+fn tick_nav_link_cooldowns(
+    mut cooldowns: Query<(Entity, &mut NavLinkCooldown)>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut cooldown) in &mut cooldowns {
+        if cooldown.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<NavLinkCooldown>();
+        }
+    }
+}
+
+/// Climbing NPCs are moved by hand, so their controller and gravity are switched back on once they are done.
+fn stop_climbing(
+    trigger: Trigger<OnRemove, UsingNavLink>,
+    using_link: Query<&UsingNavLink>,
+    mut commands: Commands,
+) {
+    let npc = trigger.target();
+    if using_link.get(npc).is_ok_and(UsingNavLink::is_climbing) {
+        commands
+            .entity(npc)
+            .try_insert((TnuaToggle::Enabled, GravityScale(1.0)));
+    }
+}
+
+/// The velocity of a climbing NPC: straight up until it is above the end of the link, then onto it.
+pub(crate) fn climb_velocity(link: &NavLink, feet: Vec3) -> Vec3 {
+    if feet.y < link.end.y + CLIMB_CLEARANCE {
+        Vec3::Y * CLIMB_SPEED
+    } else {
+        (link.end - feet).with_y(0.0).normalize_or_zero() * CLIMB_SPEED
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::headless::{headless_app, run_for};
+
+    /// The island the NPC starts on ends here, and a higher one starts a few meters further.
+    const ISLAND_EDGE: f32 = 6.5;
+    const DESTINATION: Vec3 = Vec3::new(15.0, 2.0, 0.0);
+    const WALK_SPEED: f32 = 4.0;
+
+    fn island(point: Vec3) -> usize {
+        usize::from(point.x > ISLAND_EDGE)
+    }
+
+    /// Stands in for the NPC following the destination.
+    fn target_destination(mut targets: Query<&mut AgentTarget3d>) {
+        for mut target in &mut targets {
+            *target = AgentTarget3d::Point(DESTINATION);
+        }
+    }
+
+    /// Stands in for landmass, which can only find paths on the island the NPC is on.
+    fn find_paths(
+        mut agents: Query<(&AgentOf, &AgentTarget3d, &mut AgentState)>,
+        npcs: Query<&Transform>,
+    ) {
+        for (agent_of, target, mut state) in &mut agents {
+            let (Ok(npc), AgentTarget3d::Point(target)) = (npcs.get(**agent_of), target) else {
+                continue;
+            };
+            *state = if island(npc.translation) == island(*target) {
+                AgentState::Moving
+            } else {
+                AgentState::NoPath
+            };
+        }
+    }
+
+    /// Stands in for the character controller, walking to the target or across the link.
+    fn walk(
+        agents: Query<(&AgentOf, &AgentTarget3d, &AgentState)>,
+        mut npcs: Query<(&mut Transform, &NpcStats, Option<&UsingNavLink>)>,
+        time: Res<Time>,
+    ) {
+        for (agent_of, target, state) in &agents {
+            let Ok((mut transform, stats, using_link)) = npcs.get_mut(**agent_of) else {
+                continue;
+            };
+            let goal = match (using_link, target) {
+                (Some(using_link), _) if using_link.crossing => using_link.link.end,
+                (_, AgentTarget3d::Point(target)) if matches!(state, AgentState::Moving) => *target,
+                _ => continue,
+            };
+            let step = WALK_SPEED * time.delta_secs();
+            transform.translation = transform
+                .translation
+                .move_towards(goal + Vec3::Y * stats.float_height(), step);
+        }
+    }
+
+    #[test]
+    fn npc_on_island_reaches_target_over_link() {
+        let mut app = headless_app();
+        app.add_systems(
+            Update,
+            (target_destination, target_nav_links, find_paths, walk).chain(),
+        );
+        let float_height = NpcStats::default().float_height();
+        let start = Vec3::new(-5.0, float_height, 0.0);
+        let npc = app
+            .world_mut()
+            .spawn((
+                Transform::from_translation(start),
+                AiState::Chase,
+                NpcStats::default(),
+            ))
+            .id();
+        app.world_mut().spawn((
+            AgentOf(npc),
+            AgentTarget3d::Point(DESTINATION),
+            AgentState::NoPath,
+        ));
+        app.world_mut().spawn(NavLink {
+            start: Vec3::new(4.0, 0.0, 0.0),
+            end: Vec3::new(9.0, 2.0, 0.0),
+            kind: NavLinkKind::Jump,
+        });
+
+        run_for(&mut app, Duration::from_secs(10));
+
+        let position = app.world().get::<Transform>(npc).unwrap().translation;
+        let feet = position - Vec3::Y * float_height;
+        assert!(
+            feet.distance(DESTINATION) < 0.1,
+            "The NPC got stuck at {feet}"
+        );
+        assert!(!app.world().entity(npc).contains::<UsingNavLink>());
+    }
+}
@@ -12,8 +12,9 @@ use bevy_trenchbroom::prelude::*;
 
 use crate::{
     gameplay::{
-        explosion::{ExplodeOnDeath, Explosive},
-        npc::stats::NpcStats,
+        explosion::{ExplodeOnDeath, ExplosionFalloff, Explosive},
+        npc::{behavior::Decision, perception::Perception, stats::NpcStats},
+        rng::{GameplayRng, RngStream},
     },
     third_party::{avian3d::CollisionLayer, bevy_trenchbroom::LoadTrenchbroomModel as _},
 };
@@ -22,11 +23,18 @@ use super::{animation::AnimationPlayerAncestor, health::Health};
 pub(crate) mod ai_state;
 mod animation;
 mod assets;
-mod attack;
+pub(crate) mod attack;
+pub(crate) mod behavior;
+pub(crate) mod danger;
 pub(crate) mod despawn_hacks;
+pub(crate) mod hit_zones;
 pub(crate) mod lifecycle;
+pub(crate) mod links;
 pub(crate) mod navigation;
+pub(crate) mod perception;
+pub(crate) mod slots;
 mod sound;
+pub(crate) mod spit;
 pub(crate) mod stats;
 
 pub(super) fn plugin(app: &mut App) {
@@ -40,6 +48,11 @@ pub(super) fn plugin(app: &mut App) {
         lifecycle::plugin,
         stats::plugin,
         despawn_hacks::plugin,
+        hit_zones::plugin,
+        spit::plugin,
+        perception::plugin,
+        behavior::plugin,
+        (danger::plugin, slots::plugin, links::plugin),
     ));
     app.register_type::<Npc>();
     app.add_observer(on_add);
@@ -49,7 +62,7 @@ pub(super) fn plugin(app: &mut App) {
 #[reflect(QuakeClass, Component)]
 #[base(Transform, Visibility)]
 #[model("models/zombie_3/zombie_3.gltf")]
-#[require(NpcStats)]
+#[require(NpcStats, Perception, Decision)]
 // In Wasm, TrenchBroom classes are not automatically registered.
 // So, we need to manually register the class in `src/third_party/bevy_trenchbroom/mod.rs`.
 pub(crate) struct Npc;
@@ -62,12 +75,15 @@ pub(crate) const NPC_HEIGHT: f32 = NPC_CAPSULE_LENGTH + 2.0 * NPC_RADIUS;
 fn on_add(
     trigger: Trigger<OnAdd, NpcStats>,
     stats: Query<&NpcStats>,
+    mut rng: ResMut<GameplayRng>,
     mut commands: Commands,
     assets: Res<AssetServer>,
 ) {
     let Ok(stats) = stats.get(trigger.target()) else {
         return;
     };
+    // New NPCs have yet to spot the player. Their perception may still give them a lead to investigate.
+    let ai_state = AiState::idle(rng.stream(RngStream::Npc));
     let radius = stats.radius();
     let capsule_length = stats.capsule_length();
     let npc_float_height = stats.float_height();
@@ -88,10 +104,14 @@ fn on_add(
                 LayerMask::ALL,
             ),
             Health::new(100.0),
-            AiState::default(),
+            ai_state,
             ExplodeOnDeath,
             Explosive {
                 radius: stats.size * 2.5,
+                // Enemies right next to the explosion take the full hit,
+                // so that chains stay reliable in tight crowds.
+                inner_radius: stats.size * 1.0,
+                falloff: ExplosionFalloff::Linear,
                 impulse_strength: 5.0,
                 // Scale the damage based on the NPC size
                 // so that killing a larger NPC is more impactful.
@@ -99,10 +119,15 @@ fn on_add(
                 damages_player: false,
             },
         ))
-        .with_child((
-            Name::new("Npc Model"),
-            SceneRoot(assets.load_trenchbroom_model::<Npc>()),
-            Transform::from_xyz(0.0, -npc_float_height, 0.0).with_scale(Vec3::splat(stats.size)),
-        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    Name::new("Npc Model"),
+                    SceneRoot(assets.load_trenchbroom_model::<Npc>()),
+                    Transform::from_xyz(0.0, -npc_float_height, 0.0)
+                        .with_scale(Vec3::splat(stats.size)),
+                ))
+                .observe(hit_zones::add_hit_zones);
+        })
         .observe(setup_npc_animations);
 }
@@ -5,7 +5,7 @@ use std::f32::consts::TAU;
 use avian3d::prelude::*;
 use bevy::prelude::*;
 use bevy_landmass::{
-    TargetReachedCondition,
+    AgentTypeIndexCostOverrides, TargetReachedCondition,
     prelude::{
         AgentDesiredVelocity3d as LandmassAgentDesiredVelocity, Velocity3d as LandmassVelocity, *,
     },
@@ -16,10 +16,19 @@ use bevy_tnua::prelude::*;
 
 use crate::{
     PrePhysicsAppSystems,
-    gameplay::{npc::stats::NpcStats, player::navmesh_position::LastValidPlayerNavmeshPosition},
+    gameplay::{
+        npc::stats::NpcStats,
+        player::{Player, navmesh_position::LastValidPlayerNavmeshPosition},
+    },
+    third_party::bevy_landmass::DANGER_TYPE_INDEX,
 };
 
-use super::{ai_state::AiState, attack::Attacking};
+use super::{
+    ai_state::AiState,
+    attack::Attacking,
+    links::{UsingNavLink, climb_velocity},
+    slots::{SurroundSlot, assign_slots},
+};
 
 pub(crate) const NPC_MAX_SLOPE: f32 = TAU / 6.0;
 
@@ -36,7 +45,9 @@ pub(super) fn plugin(app: &mut App) {
     );
     app.add_systems(
         RunFixedMainLoop,
-        update_agent_target.in_set(PrePhysicsAppSystems::UpdateNavmeshTargets),
+        update_agent_target
+            .after(assign_slots)
+            .in_set(PrePhysicsAppSystems::UpdateNavmeshTargets),
     );
     app.add_observer(setup_npc_agent);
 }
@@ -66,38 +77,75 @@ fn setup_npc_agent(
             },
             archipelago_ref: ArchipelagoRef3d::new(*archipelago),
         },
-        TargetReachedCondition::Distance(Some(1.5 * stats.size)),
+        // Ranged NPCs stop as soon as they are close enough to spit.
+        TargetReachedCondition::Distance(Some(
+            stats
+                .ranged
+                .as_ref()
+                .map_or(1.5 * stats.size, |ranged| ranged.preferred_distance),
+        )),
         ChildOf(npc),
         AgentOf(npc),
         AgentTarget3d::default(),
         WantsToFollowPlayer,
+        danger_cost_overrides(stats),
     ));
 }
 
+/// Careful archetypes pay extra for walking through [`DangerZoneOf`](super::danger::DangerZoneOf)s.
+fn danger_cost_overrides(stats: &NpcStats) -> AgentTypeIndexCostOverrides {
+    let mut overrides = AgentTypeIndexCostOverrides::default();
+    if stats.danger_cost != 1.0 {
+        overrides.set_type_index_cost(DANGER_TYPE_INDEX, stats.danger_cost.max(f32::EPSILON));
+    }
+    overrides
+}
+
 #[derive(Component, Debug, Reflect)]
 #[reflect(Component)]
 struct WantsToFollowPlayer;
 
 #[cfg_attr(feature = "hot_patch", hot)]
-fn update_agent_target(
+pub(super) fn update_agent_target(
     mut agents: Query<(&mut AgentTarget3d, &AgentOf), With<WantsToFollowPlayer>>,
-    ai_state: Query<(&Transform, &AiState)>,
+    ai_state: Query<(&Transform, &AiState, &NpcStats, Option<&SurroundSlot>)>,
     player_position: Single<&LastValidPlayerNavmeshPosition>,
 ) {
     let Some(player_position) = player_position.0 else {
         return;
     };
     for (mut target, agent_of) in &mut agents {
-        let Ok((ai_transform, ai_state)) = ai_state.get(agent_of.0) else {
+        let Ok((ai_transform, ai_state, stats, slot)) = ai_state.get(agent_of.0) else {
             continue;
         };
         match ai_state {
             AiState::Chase => {
-                *target = AgentTarget3d::Point(player_position);
+                let point = slot.map_or(player_position, |slot| {
+                    slot.target(ai_transform.translation, player_position)
+                });
+                *target = AgentTarget3d::Point(point);
+            }
+            AiState::Wander(point) | AiState::Investigate(point) => {
+                *target = AgentTarget3d::Point(*point);
             }
-            AiState::Stagger(..) | AiState::Attack => {
+            AiState::Idle(..)
+            | AiState::Alerted(..)
+            | AiState::Stagger(..)
+            | AiState::Attack
+            | AiState::Aim(..) => {
                 *target = AgentTarget3d::Point(ai_transform.translation);
             }
+            AiState::Reposition(..) => {
+                let away = (ai_transform.translation - player_position)
+                    .with_y(0.0)
+                    .normalize_or_zero();
+                // Aim past the preferred distance, as the target counts as reached within it.
+                let distance = stats
+                    .ranged
+                    .as_ref()
+                    .map_or(0.0, |ranged| 2.0 * ranged.preferred_distance);
+                *target = AgentTarget3d::Point(ai_transform.translation + away * distance);
+            }
         }
     }
 }
@@ -105,26 +153,70 @@ fn update_agent_target(
 #[derive(Component, Deref, DerefMut, Debug, Reflect)]
 #[reflect(Component)]
 #[relationship(relationship_target = Agent)]
-pub(crate) struct AgentOf(Entity);
+pub(crate) struct AgentOf(pub(crate) Entity);
 
 #[derive(Component, Deref, DerefMut, Debug, Reflect)]
 #[reflect(Component)]
 #[relationship_target(relationship = AgentOf)]
 pub(crate) struct Agent(Entity);
 
-/// Use the desired velocity as the agent's velocity.
+/// Use the desired velocity as the agent's velocity, unless the NPC is crossing an off-mesh link.
 #[cfg_attr(feature = "hot_patch", hot)]
 fn set_controller_velocity(
-    mut agent_query: Query<(&mut TnuaController, &Agent, Option<&Attacking>, &NpcStats)>,
+    mut agent_query: Query<(
+        &mut TnuaController,
+        &mut LinearVelocity,
+        &Agent,
+        Option<&Attacking>,
+        Option<&mut UsingNavLink>,
+        &AiState,
+        &Transform,
+        &NpcStats,
+    )>,
     desired_velocity_query: Query<&LandmassAgentDesiredVelocity>,
+    player: Query<&Transform, With<Player>>,
 ) {
-    for (mut controller, agent, attacking, stats) in &mut agent_query {
+    for (
+        mut controller,
+        mut linear_velocity,
+        agent,
+        attacking,
+        mut using_link,
+        ai_state,
+        transform,
+        stats,
+    ) in &mut agent_query
+    {
         let Ok(desired_velocity) = desired_velocity_query.get(**agent) else {
             continue;
         };
-        let velocity = desired_velocity.velocity();
+        let crossing = using_link
+            .as_deref_mut()
+            .filter(|using_link| using_link.crossing);
+        if let Some(using_link) = &crossing
+            && using_link.is_climbing()
+        {
+            // The controller is switched off while climbing.
+            let feet = transform.translation - Vec3::Y * stats.float_height();
+            linear_velocity.0 = climb_velocity(&using_link.link, feet);
+            continue;
+        }
+        let velocity = match &crossing {
+            // Landmass does not know about links, so head straight for the end.
+            Some(using_link) => {
+                (using_link.link.end - transform.translation)
+                    .with_y(0.0)
+                    .normalize_or_zero()
+                    * stats.desired_speed
+            }
+            None => desired_velocity.velocity(),
+        };
         let forward = if let Some(attacking) = attacking {
             attacking.dir
+        } else if let AiState::Aim(..) | AiState::Alerted(..) = ai_state
+            && let Ok(player) = player.single()
+        {
+            Dir3::try_from((player.translation - transform.translation).with_y(0.0)).ok()
         } else {
             Dir3::try_from(velocity).ok()
         };
@@ -137,6 +229,20 @@ fn set_controller_velocity(
             max_slope: NPC_MAX_SLOPE,
             ..default()
         });
+
+        if let Some(using_link) = crossing
+            && let Some(height) = using_link.link.jump_height()
+        {
+            let airborne = controller.is_airborne().unwrap_or(false);
+            using_link.jumped |= airborne;
+            // Keep holding the jump until the apex for its full height, but don't jump again after landing.
+            if !using_link.jumped || (airborne && linear_velocity.y > 0.0) {
+                controller.action(TnuaBuiltinJump {
+                    height,
+                    ..default()
+                });
+            }
+        }
     }
 }
 
@@ -0,0 +1,265 @@
+//! What NPCs know about the player.
+//!
+//! NPCs see the player inside a cone in front of them when nothing blocks the view, notice them when they get
+//! very close, and hear [`OnNoise`]s like gunshots and explosions. Where the player was last seen or heard is
+//! kept in their [`Perception`], which their [`Behavior`](super::behavior::Behavior) uses to decide whether to chase
+//! the player or go looking for them. NPCs that join later are drawn to the last noise of the fight.
+
+use std::f32::consts::PI;
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+#[cfg(feature = "hot_patch")]
+use bevy_simple_subsecond_system::hot;
+
+use crate::{
+    gameplay::{
+        explosion::OnExplode,
+        health::{DamageKind, OnDamage},
+        npc::stats::NpcStats,
+        player::{Player, gunplay::Shooting},
+    },
+    screens::Screen,
+    third_party::avian3d::CollisionLayer,
+};
+
+/// How far away a gunshot can be heard.
+const GUNSHOT_NOISE_RADIUS: f32 = 35.0;
+/// How far away an explosion can be heard.
+const EXPLOSION_NOISE_RADIUS: f32 = 50.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(Perception, LastNoise)>();
+    app.init_resource::<LastNoise>();
+    app.add_systems(OnEnter(Screen::Gameplay), reset_last_noise);
+    app.add_systems(PreUpdate, perceive);
+    app.add_observer(hear_noise);
+    app.add_observer(follow_last_noise);
+    app.add_observer(gunshot_noise);
+    app.add_observer(explosion_noise);
+    app.add_observer(notice_attacker);
+}
+
+/// The senses of an NPC and what it knows about the player.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub(crate) struct Perception {
+    /// How far the NPC can see.
+    pub(crate) sight_range: f32,
+    /// Half of the opening angle of the sight cone, in radians.
+    pub(crate) sight_angle: f32,
+    /// The NPC notices the player within this distance, even without seeing them.
+    pub(crate) proximity_range: f32,
+    /// Multiplies the radius of the noises the NPC hears.
+    pub(crate) hearing: f32,
+    /// How long the NPC keeps chasing the player after it last saw or heard them, in seconds.
+    pub(crate) memory_secs: f32,
+    sees_player: bool,
+    last_known_position: Option<Vec3>,
+    since_noticed: f32,
+}
+
+impl Default for Perception {
+    fn default() -> Self {
+        Self {
+            sight_range: 30.0,
+            sight_angle: PI / 3.0,
+            proximity_range: 3.0,
+            hearing: 1.0,
+            memory_secs: 4.0,
+            sees_player: false,
+            last_known_position: None,
+            since_noticed: f32::INFINITY,
+        }
+    }
+}
+
+impl Perception {
+    pub(crate) fn sees_player(&self) -> bool {
+        self.sees_player
+    }
+
+    /// Where the player was last seen or heard.
+    pub(crate) fn last_known_position(&self) -> Option<Vec3> {
+        self.last_known_position
+    }
+
+    /// Whether the player was neither seen nor heard for longer than the NPC remembers.
+    pub(crate) fn lost_track(&self) -> bool {
+        self.since_noticed > self.memory_secs
+    }
+
+    fn notice(&mut self, position: Vec3) {
+        self.last_known_position = Some(position);
+        self.since_noticed = 0.0;
+    }
+
+    /// A noise keeps an NPC that is after the player on their trail, and gives others a lead to investigate.
+    fn hear(&mut self, position: Vec3) {
+        if self.lost_track() {
+            self.last_known_position = Some(position);
+        } else {
+            self.notice(position);
+        }
+    }
+
+    /// Drops the lead on the player, e.g. after investigating it turned up nothing.
+    pub(crate) fn forget(&mut self) {
+        self.last_known_position = None;
+    }
+}
+
+/// Triggered when something makes a noise that NPCs within the radius can hear.
+#[derive(Event, Debug, Clone, Copy)]
+pub(crate) struct OnNoise {
+    pub(crate) position: Vec3,
+    pub(crate) radius: f32,
+}
+
+/// Where the most recent [`OnNoise`] of the run was made.
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub(crate) struct LastNoise(pub(crate) Option<Vec3>);
+
+/// Whether nothing solid is between the two points.
+pub(crate) fn has_line_of_sight(spatial_query: &SpatialQuery, from: Vec3, to: Vec3) -> bool {
+    let Ok((direction, distance)) = Dir3::new_and_length(to - from) else {
+        return true;
+    };
+    let filter = SpatialQueryFilter::default().with_mask(CollisionLayer::Default);
+    spatial_query
+        .cast_ray(from, direction, distance, true, &filter)
+        .is_none()
+}
+
+/// The point NPCs look out of.
+pub(crate) fn eye_position(transform: &Transform, stats: &NpcStats) -> Vec3 {
+    transform.translation + Vec3::Y * stats.half_height() * 0.6
+}
+
+/// Whether the direction is within `angle` of `forward`, ignoring height differences.
+fn in_sight_cone(forward: Vec3, direction: Vec3, angle: f32) -> bool {
+    let (forward, direction) = (forward.with_y(0.0), direction.with_y(0.0));
+    direction.length_squared() < f32::EPSILON || forward.angle_between(direction) <= angle
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+pub(super) fn perceive(
+    mut npcs: Query<(&mut Perception, &Transform, &NpcStats)>,
+    player: Single<&Transform, With<Player>>,
+    spatial_query: SpatialQuery,
+    time: Res<Time>,
+) {
+    for (mut perception, transform, stats) in &mut npcs {
+        perception.since_noticed += time.delta_secs();
+        let eye = eye_position(transform, stats);
+        let to_player = player.translation - eye;
+        let distance = to_player.length();
+        perception.sees_player = distance <= perception.proximity_range
+            || (distance <= perception.sight_range
+                && in_sight_cone(*transform.forward(), to_player, perception.sight_angle)
+                && has_line_of_sight(&spatial_query, eye, player.translation));
+        if perception.sees_player {
+            perception.notice(player.translation);
+        }
+    }
+}
+
+fn hear_noise(
+    trigger: Trigger<OnNoise>,
+    mut npcs: Query<(&mut Perception, &Transform)>,
+    mut last_noise: ResMut<LastNoise>,
+) {
+    let noise = trigger.event();
+    last_noise.0 = Some(noise.position);
+    for (mut perception, transform) in &mut npcs {
+        if transform.translation.distance(noise.position) <= noise.radius * perception.hearing {
+            perception.hear(noise.position);
+        }
+    }
+}
+
+/// New NPCs did not hear the fight, but get a lead on where it happened.
+fn follow_last_noise(
+    trigger: Trigger<OnAdd, Perception>,
+    mut perceptions: Query<&mut Perception>,
+    last_noise: Res<LastNoise>,
+) {
+    if let Ok(mut perception) = perceptions.get_mut(trigger.target()) {
+        perception.last_known_position = last_noise.0;
+    }
+}
+
+fn reset_last_noise(mut last_noise: ResMut<LastNoise>) {
+    last_noise.0 = None;
+}
+
+fn gunshot_noise(
+    trigger: Trigger<OnAdd, Shooting>,
+    transforms: Query<&Transform>,
+    mut commands: Commands,
+) {
+    let Ok(transform) = transforms.get(trigger.target()) else {
+        return;
+    };
+    commands.trigger(OnNoise {
+        position: transform.translation,
+        radius: GUNSHOT_NOISE_RADIUS,
+    });
+}
+
+fn explosion_noise(
+    trigger: Trigger<OnExplode>,
+    transforms: Query<&GlobalTransform>,
+    mut commands: Commands,
+) {
+    let Ok(transform) = transforms.get(trigger.target()) else {
+        return;
+    };
+    commands.trigger(OnNoise {
+        position: transform.translation(),
+        radius: EXPLOSION_NOISE_RADIUS,
+    });
+}
+
+/// NPCs that get shot know where the shot came from.
+fn notice_attacker(
+    trigger: Trigger<OnDamage>,
+    mut npcs: Query<&mut Perception>,
+    transforms: Query<&Transform>,
+) {
+    if trigger.kind != DamageKind::Bullet {
+        return;
+    }
+    let Ok(mut perception) = npcs.get_mut(trigger.target()) else {
+        return;
+    };
+    let Some(attacker) = trigger
+        .instigator
+        .and_then(|entity| transforms.get(entity).ok())
+    else {
+        return;
+    };
+    perception.notice(attacker.translation);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sight_cone_ignores_height() {
+        let angle = PI / 4.0;
+        assert!(in_sight_cone(
+            Vec3::NEG_Z,
+            Vec3::new(0.5, 10.0, -1.0),
+            angle
+        ));
+        assert!(!in_sight_cone(
+            Vec3::NEG_Z,
+            Vec3::new(1.5, 0.0, -1.0),
+            angle
+        ));
+        assert!(!in_sight_cone(Vec3::NEG_Z, Vec3::Z, angle));
+    }
+}
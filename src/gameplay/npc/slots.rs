@@ -0,0 +1,232 @@
+//! Spreading hordes around the player.
+//!
+//! Instead of all running for the same point, every NPC that is after the player in melee claims a
+//! [`SurroundSlot`] on a ring around them. Slots are as wide as the NPC, so the ring of a big enemy holds fewer
+//! of them. NPCs keep their slot while they can, pick the free one closest to the side they are coming from
+//! otherwise, and line up on an outer ring once the inner one is full. On the way, NPCs first head for an
+//! approach point further out on the same side, so that they fan out long before reaching the player.
+
+use std::{f32::consts::PI, time::Duration};
+
+use bevy::prelude::*;
+use bevy_landmass::{Archipelago3d, FromAgentRadius, PointSampleDistance3d};
+#[cfg(feature = "hot_patch")]
+use bevy_simple_subsecond_system::hot;
+
+use crate::{
+    PrePhysicsAppSystems,
+    gameplay::{
+        npc::{ai_state::AiState, stats::NpcStats},
+        player::{PLAYER_RADIUS, navmesh_position::LastValidPlayerNavmeshPosition},
+    },
+};
+
+/// How often the slots are reassigned, in milliseconds.
+const REASSIGN_MILLIS: u64 = 250;
+/// The space left between neighboring NPCs and between the player and the innermost ring.
+const SLOT_GAP: f32 = 0.3;
+/// An NPC gives up its slot when the player moved so much that it is now coming from a different side.
+const MAX_SLOT_DRIFT: f32 = PI / 2.0;
+/// How many rings of NPCs can line up around the player.
+const MAX_RINGS: usize = 3;
+/// How much further out than the slot the approach point is.
+const APPROACH_DISTANCE: f32 = 4.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<SurroundSlot>();
+    app.add_systems(
+        RunFixedMainLoop,
+        assign_slots.in_set(PrePhysicsAppSystems::UpdateNavmeshTargets),
+    );
+}
+
+/// The place around the player claimed by an NPC. The points are on the navmesh.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub(crate) struct SurroundSlot {
+    /// The direction of the slot as seen from the player, in radians around the Y axis.
+    pub(crate) angle: f32,
+    /// How far from the player the slot is.
+    pub(crate) ring: f32,
+    /// Where the NPC stands to attack the player.
+    pub(crate) attack_point: Vec3,
+    /// Where the NPC heads to while it is still far away. `None` if that point is not on the navmesh.
+    pub(crate) approach_point: Option<Vec3>,
+}
+
+impl SurroundSlot {
+    /// The point the NPC should currently walk to.
+    pub(crate) fn target(&self, npc_translation: Vec3, player_position: Vec3) -> Vec3 {
+        // Switch to the attack point well before reaching the approach point, so that the NPC never stops there.
+        let still_far =
+            npc_translation.distance(player_position) > self.ring + 2.0 * APPROACH_DISTANCE;
+        match self.approach_point {
+            Some(approach_point) if still_far => approach_point,
+            _ => self.attack_point,
+        }
+    }
+}
+
+struct SlotRequest {
+    /// The direction the NPC is coming from.
+    bearing: f32,
+    previous: Option<f32>,
+    radius: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Slot {
+    angle: f32,
+    ring: f32,
+    radius: f32,
+}
+
+impl Slot {
+    fn overlaps(&self, other: &Slot) -> bool {
+        let distance =
+            slot_offset(self.angle, self.ring).distance(slot_offset(other.angle, other.ring));
+        distance < self.radius + other.radius + SLOT_GAP
+    }
+}
+
+fn slot_offset(angle: f32, ring: f32) -> Vec3 {
+    Vec3::new(angle.cos(), 0.0, angle.sin()) * ring
+}
+
+/// The difference between two angles, in `[-PI, PI]`.
+fn angle_difference(a: f32, b: f32) -> f32 {
+    (a - b + PI).rem_euclid(2.0 * PI) - PI
+}
+
+/// Hands out non-overlapping slots, in the order of the requests.
+fn allocate_slots(requests: &[SlotRequest]) -> Vec<Slot> {
+    let mut claimed: Vec<Slot> = Vec::with_capacity(requests.len());
+    for request in requests {
+        let desired = request
+            .previous
+            .filter(|previous| angle_difference(*previous, request.bearing).abs() < MAX_SLOT_DRIFT)
+            .unwrap_or(request.bearing);
+        let innermost_ring = PLAYER_RADIUS + request.radius + SLOT_GAP;
+        let slot = (0..MAX_RINGS)
+            .find_map(|ring_index| {
+                let ring = innermost_ring + ring_index as f32 * (2.0 * request.radius + SLOT_GAP);
+                // Try angles alternating to both sides of the desired one, in steps of half a slot.
+                let step = (request.radius + SLOT_GAP / 2.0) / ring;
+                let steps = (PI / step) as i32;
+                (0..=steps)
+                    .flat_map(|i| [i, -i])
+                    .map(|i| Slot {
+                        angle: desired + i as f32 * step,
+                        ring,
+                        radius: request.radius,
+                    })
+                    .find(|slot| claimed.iter().all(|other| !slot.overlaps(other)))
+            })
+            // When all rings are full, crowd in anyway.
+            .unwrap_or(Slot {
+                angle: desired,
+                ring: innermost_ring,
+                radius: request.radius,
+            });
+        claimed.push(slot);
+    }
+    claimed
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+pub(super) fn assign_slots(
+    npcs: Query<(
+        Entity,
+        &Transform,
+        &AiState,
+        &NpcStats,
+        Option<&SurroundSlot>,
+    )>,
+    player_position: Single<&LastValidPlayerNavmeshPosition>,
+    archipelago: Single<&Archipelago3d>,
+    time: Res<Time>,
+    mut timer: Local<Option<Timer>>,
+    mut commands: Commands,
+) {
+    let timer = timer.get_or_insert(Timer::new(
+        Duration::from_millis(REASSIGN_MILLIS),
+        TimerMode::Repeating,
+    ));
+    if !timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let Some(player_position) = player_position.0 else {
+        return;
+    };
+
+    let mut claimants = Vec::new();
+    for (entity, transform, ai_state, stats, slot) in &npcs {
+        // Ranged NPCs keep their distance anyway.
+        if ai_state.is_unaware() || stats.ranged.is_some() {
+            if slot.is_some() {
+                commands.entity(entity).remove::<SurroundSlot>();
+            }
+            continue;
+        }
+        let to_npc = transform.translation - player_position;
+        let request = SlotRequest {
+            bearing: to_npc.z.atan2(to_npc.x),
+            previous: slot.map(|slot| slot.angle),
+            radius: stats.radius(),
+        };
+        claimants.push((entity, to_npc.xz().length(), request));
+    }
+    // NPCs that already have a slot keep it, and closer NPCs get first pick of the rest.
+    claimants.sort_by(|(_, a_distance, a), (_, b_distance, b)| {
+        (a.previous.is_none().cmp(&b.previous.is_none())).then(a_distance.total_cmp(b_distance))
+    });
+    let (entities, requests): (Vec<_>, Vec<_>) = claimants
+        .into_iter()
+        .map(|(entity, _, request)| (entity, request))
+        .unzip();
+    let slots = allocate_slots(&requests);
+
+    for (entity, slot) in entities.into_iter().zip(slots) {
+        let sample_distance = PointSampleDistance3d::from_agent_radius(slot.radius);
+        let sample = |offset: Vec3| {
+            archipelago
+                .sample_point(player_position + offset, &sample_distance)
+                .ok()
+                .map(|point| point.point())
+        };
+        // A slot in a wall falls back to the player themselves.
+        let attack_point = sample(slot_offset(slot.angle, slot.ring)).unwrap_or(player_position);
+        let approach_point = sample(slot_offset(slot.angle, slot.ring + APPROACH_DISTANCE));
+        commands.entity(entity).insert(SurroundSlot {
+            angle: slot.angle,
+            ring: slot.ring,
+            attack_point,
+            approach_point,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slots_do_not_overlap() {
+        // A crowd all coming from the same side.
+        let requests: Vec<_> = (0..8)
+            .map(|i| SlotRequest {
+                bearing: 0.0,
+                previous: None,
+                radius: if i % 3 == 0 { 0.8 } else { 0.4 },
+            })
+            .collect();
+        let slots = allocate_slots(&requests);
+        for (i, a) in slots.iter().enumerate() {
+            for b in &slots[i + 1..] {
+                assert!(!a.overlaps(b), "{a:?} overlaps {b:?}");
+            }
+        }
+        // The first one gets the spot right in front of it.
+        assert_eq!(slots[0].angle, 0.0);
+    }
+}
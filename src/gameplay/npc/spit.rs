@@ -0,0 +1,296 @@
+//! The ranged attack of spitters.
+//!
+//! A spitter keeps its distance from the player and, whenever it can see them, lobs a glob of acid in an arc
+//! towards where the player is headed. The glob damages the player on a direct hit and leaves an [`AcidPuddle`]
+//! on the ground wherever it lands.
+
+use std::time::Duration;
+
+use avian3d::prelude::*;
+use bevy::{pbr::NotShadowCaster, prelude::*};
+#[cfg(feature = "hot_patch")]
+use bevy_simple_subsecond_system::hot;
+
+use crate::{
+    despawn_after::DespawnAfter,
+    gameplay::{
+        health::{DamageKind, OnDamage},
+        npc::{perception::eye_position, stats::NpcStats},
+        player::Player,
+    },
+    screens::Screen,
+    third_party::avian3d::CollisionLayer,
+};
+
+const SPIT_RADIUS: f32 = 0.15;
+/// Spit that flies for longer than this has left the level.
+const SPIT_LIFETIME_SECS: u64 = 10;
+/// The shortest time a glob of spit is in the air, so that close shots still arc a little.
+const MIN_FLIGHT_SECS: f32 = 0.3;
+const PUDDLE_RADIUS: f32 = 1.2;
+/// How far below a splash the ground can be for a puddle to form.
+const PUDDLE_MAX_DROP: f32 = 3.0;
+/// How often a puddle damages the player standing in it, in seconds.
+const PUDDLE_TICK_SECS: f32 = 0.5;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(RangedAttack, SpitCooldown, Spit, AcidPuddle)>();
+    app.init_resource::<SpitAssets>();
+    app.add_observer(spit);
+    app.add_systems(
+        Update,
+        (tick_spit_cooldowns, damage_in_puddles).run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// How an NPC archetype attacks from afar. The damage of a direct hit is [`NpcStats::attack_damage`].
+#[derive(Debug, Clone, Reflect)]
+pub(crate) struct RangedAttack {
+    /// The distance the NPC tries to keep from the player.
+    pub(crate) preferred_distance: f32,
+    /// When the player gets closer than this, the NPC backs away.
+    pub(crate) min_distance: f32,
+    /// How long the NPC aims before spitting, in seconds.
+    pub(crate) aim_secs: f32,
+    /// How long the NPC waits after spitting before it can aim again, in seconds.
+    pub(crate) cooldown_secs: f32,
+    /// The horizontal speed of the spit.
+    pub(crate) projectile_speed: f32,
+    /// The damage a puddle deals every [`PUDDLE_TICK_SECS`] to the player standing in it.
+    pub(crate) puddle_damage: f32,
+    /// How long a puddle stays on the ground, in seconds.
+    pub(crate) puddle_secs: f32,
+}
+
+/// Triggered on an NPC with a [`RangedAttack`] to make it spit at the player.
+#[derive(Event)]
+pub(crate) struct OnSpit;
+
+/// The NPC spat recently and cannot aim again until the timer finishes.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub(crate) struct SpitCooldown(Timer);
+
+/// A glob of spit in flight.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub(crate) struct Spit {
+    damage: f32,
+    instigator: Entity,
+    puddle_damage: f32,
+    puddle_secs: f32,
+}
+
+/// Acid left behind by [`Spit`], hurting the player while they stand in it.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub(crate) struct AcidPuddle {
+    damage: f32,
+    instigator: Entity,
+    tick: Timer,
+}
+
+#[derive(Resource)]
+struct SpitAssets {
+    spit_mesh: Handle<Mesh>,
+    spit_material: Handle<StandardMaterial>,
+    puddle_mesh: Handle<Mesh>,
+    puddle_material: Handle<StandardMaterial>,
+}
+
+impl FromWorld for SpitAssets {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            spit_mesh: world.add_asset(Sphere::new(SPIT_RADIUS).mesh().ico(2).unwrap()),
+            spit_material: world.add_asset(StandardMaterial {
+                base_color: Color::srgb(0.5, 0.9, 0.1),
+                emissive: LinearRgba::rgb(0.8, 2.0, 0.1),
+                ..default()
+            }),
+            puddle_mesh: world.add_asset(Cylinder::new(PUDDLE_RADIUS, 0.02)),
+            puddle_material: world.add_asset(StandardMaterial {
+                base_color: Color::srgba(0.4, 0.8, 0.1, 0.6),
+                emissive: LinearRgba::rgb(0.2, 0.6, 0.0),
+                alpha_mode: AlphaMode::Blend,
+                perceptual_roughness: 0.2,
+                ..default()
+            }),
+        }
+    }
+}
+
+/// The velocity to launch a projectile with so that gravity carries it from one point to another,
+/// travelling at the given horizontal speed.
+fn launch_velocity(from: Vec3, to: Vec3, horizontal_speed: f32, gravity: f32) -> Vec3 {
+    let delta = to - from;
+    let horizontal = delta.with_y(0.0);
+    let time = (horizontal.length() / horizontal_speed).max(MIN_FLIGHT_SECS);
+    (horizontal / time).with_y(delta.y / time + 0.5 * gravity * time)
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn spit(
+    trigger: Trigger<OnSpit>,
+    npcs: Query<(&Transform, &NpcStats)>,
+    player: Single<(&Transform, &LinearVelocity), With<Player>>,
+    gravity: Res<Gravity>,
+    spit_assets: Res<SpitAssets>,
+    mut commands: Commands,
+) {
+    let npc = trigger.target();
+    let Ok((transform, stats)) = npcs.get(npc) else {
+        return;
+    };
+    let Some(ranged) = &stats.ranged else {
+        return;
+    };
+    let (player_transform, player_velocity) = player.into_inner();
+
+    let origin =
+        eye_position(transform, stats) + transform.forward() * (stats.radius() + SPIT_RADIUS);
+    // Lead the shot by where the player will be when it lands.
+    let flight_secs = origin.distance(player_transform.translation) / ranged.projectile_speed;
+    let target = player_transform.translation + player_velocity.with_y(0.0) * flight_secs;
+    let velocity = launch_velocity(origin, target, ranged.projectile_speed, -gravity.0.y);
+
+    commands
+        .spawn((
+            Name::new("Spit"),
+            Spit {
+                damage: stats.attack_damage,
+                instigator: npc,
+                puddle_damage: ranged.puddle_damage,
+                puddle_secs: ranged.puddle_secs,
+            },
+            Transform::from_translation(origin),
+            Mesh3d(spit_assets.spit_mesh.clone()),
+            MeshMaterial3d(spit_assets.spit_material.clone()),
+            NotShadowCaster,
+            RigidBody::Dynamic,
+            Collider::sphere(SPIT_RADIUS),
+            Sensor,
+            Mass(0.5),
+            LinearVelocity(velocity),
+            CollisionLayers::new(
+                CollisionLayer::Sensor,
+                [
+                    CollisionLayer::Default,
+                    CollisionLayer::Prop,
+                    CollisionLayer::Player,
+                ],
+            ),
+            CollisionEventsEnabled,
+            DespawnAfter::new(Duration::from_secs(SPIT_LIFETIME_SECS)),
+            StateScoped(Screen::Gameplay),
+        ))
+        .observe(splash);
+    commands
+        .entity(npc)
+        .insert(SpitCooldown(Timer::from_seconds(
+            ranged.cooldown_secs,
+            TimerMode::Once,
+        )));
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn splash(
+    trigger: Trigger<OnCollisionStart>,
+    spits: Query<(&Spit, &Position)>,
+    player: Query<(), With<Player>>,
+    spatial_query: SpatialQuery,
+    spit_assets: Res<SpitAssets>,
+    mut commands: Commands,
+) {
+    let entity = trigger.target();
+    let Ok((spit, position)) = spits.get(entity) else {
+        return;
+    };
+    commands.entity(entity).try_despawn();
+
+    if let Some(body) = trigger.event().body
+        && player.contains(body)
+    {
+        commands.entity(body).trigger(
+            OnDamage::new(spit.damage, DamageKind::Acid)
+                .with_instigator(spit.instigator)
+                .with_position(position.0),
+        );
+    }
+
+    let filter = SpatialQueryFilter::default().with_mask(CollisionLayer::Default);
+    let Some(ground) =
+        spatial_query.cast_ray(position.0, Dir3::NEG_Y, PUDDLE_MAX_DROP, true, &filter)
+    else {
+        return;
+    };
+    commands.spawn((
+        Name::new("Acid Puddle"),
+        AcidPuddle {
+            damage: spit.puddle_damage,
+            instigator: spit.instigator,
+            tick: Timer::from_seconds(PUDDLE_TICK_SECS, TimerMode::Repeating),
+        },
+        Transform::from_translation(position.0 + Vec3::NEG_Y * ground.distance + Vec3::Y * 0.02),
+        Mesh3d(spit_assets.puddle_mesh.clone()),
+        MeshMaterial3d(spit_assets.puddle_material.clone()),
+        NotShadowCaster,
+        // Tall enough to reach the floating player.
+        Collider::cylinder(PUDDLE_RADIUS, 2.0),
+        Sensor,
+        CollisionLayers::new(CollisionLayer::Sensor, CollisionLayer::Player),
+        CollidingEntities::default(),
+        DespawnAfter::new(Duration::from_secs_f32(spit.puddle_secs)),
+        StateScoped(Screen::Gameplay),
+    ));
+}
+
+fn tick_spit_cooldowns(
+    mut cooldowns: Query<(Entity, &mut SpitCooldown)>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut cooldown) in &mut cooldowns {
+        if cooldown.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<SpitCooldown>();
+        }
+    }
+}
+
+fn damage_in_puddles(
+    mut puddles: Query<(&mut AcidPuddle, &CollidingEntities, &Transform)>,
+    player: Query<(), With<Player>>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (mut puddle, colliding_entities, transform) in &mut puddles {
+        if !puddle.tick.tick(time.delta()).just_finished() {
+            continue;
+        }
+        for &entity in colliding_entities.iter() {
+            if player.contains(entity) {
+                commands.entity(entity).trigger(
+                    OnDamage::new(puddle.damage, DamageKind::Acid)
+                        .with_instigator(puddle.instigator)
+                        .with_position(transform.translation),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn launched_projectile_lands_on_target() {
+        let from = Vec3::new(1.0, 2.0, -3.0);
+        let to = Vec3::new(15.0, 0.5, 7.0);
+        let (speed, gravity) = (12.0, 9.81);
+
+        let velocity = launch_velocity(from, to, speed, gravity);
+        let time = to.with_y(0.0).distance(from.with_y(0.0)) / speed;
+        let landing = from + velocity * time + Vec3::NEG_Y * 0.5 * gravity * time * time;
+        assert!(landing.distance(to) < 1e-3, "landed at {landing}");
+    }
+}
@@ -3,11 +3,14 @@ use std::ops::Range;
 use bevy::prelude::*;
 
 use crate::gameplay::{
-    health::Health,
-    npc::{NPC_CAPSULE_LENGTH, NPC_RADIUS},
+    health::{Armor, Health, Resistances, Shield},
+    npc::{
+        NPC_CAPSULE_LENGTH, NPC_RADIUS, attack::MeleeAttack, behavior::Behavior,
+        hit_zones::HitZoneMultipliers, spit::RangedAttack,
+    },
 };
 
-pub(super) fn plugin(app: &mut App) {
+pub(crate) fn plugin(app: &mut App) {
     app.register_type::<NpcStats>();
     app.add_observer(apply_initial_stats);
 }
@@ -20,9 +23,21 @@ pub(crate) struct NpcStats {
     pub(crate) max_speed: f32,
     pub(crate) attack_damage: f32,
     pub(crate) attack_speed_range: Range<f32>,
+    pub(crate) attack: MeleeAttack,
+    /// Archetypes with a ranged attack keep their distance and never attack in melee.
+    pub(crate) ranged: Option<RangedAttack>,
+    /// What the NPC decides to do in a given situation.
+    pub(crate) behavior: Behavior,
+    /// How much more the NPC is willing to walk to avoid blast zones. At 1, it walks right through them.
+    pub(crate) danger_cost: f32,
     pub(crate) size: f32,
     pub(crate) stagger_chance: f32,
     pub(crate) stagger_duration: Range<f32>,
+    /// How much of a shot's damage each part of the body takes.
+    pub(crate) hit_zones: HitZoneMultipliers,
+    pub(crate) armor: Option<Armor>,
+    pub(crate) shield: Option<Shield>,
+    pub(crate) resistances: Resistances,
 }
 
 impl NpcStats {
@@ -55,9 +70,17 @@ impl Default for NpcStats {
             max_speed: 10.0,
             attack_damage: 10.0,
             attack_speed_range: 1.2..2.1,
+            attack: MeleeAttack::default(),
+            ranged: None,
+            behavior: Behavior::melee(),
+            danger_cost: 1.0,
             size: 1.0,
             stagger_chance: 0.1,
             stagger_duration: 0.1..0.3,
+            hit_zones: HitZoneMultipliers::default(),
+            armor: None,
+            shield: None,
+            resistances: Resistances::default(),
         }
     }
 }
@@ -71,5 +94,12 @@ fn apply_initial_stats(
     let Ok(stats) = npc.get_mut(entity) else {
         return;
     };
-    commands.entity(entity).insert(Health::new(stats.health));
+    let mut entity_commands = commands.entity(entity);
+    entity_commands.insert((Health::new(stats.health), stats.resistances.clone()));
+    if let Some(armor) = stats.armor.clone() {
+        entity_commands.insert(armor);
+    }
+    if let Some(shield) = stats.shield.clone() {
+        entity_commands.insert(shield);
+    }
 }
@@ -9,13 +9,18 @@ use bevy_tnua::{TnuaAnimatingState, TnuaAnimatingStateDirective, prelude::*};
 
 use crate::{
     PostPhysicsAppSystems,
-    gameplay::{animation::AnimationPlayers, crosshair::CrosshairState, player::gunplay::Shooting},
+    gameplay::{
+        animation::{AnimationPlayerOf, AnimationPlayers},
+        crosshair::CrosshairState,
+        player::gunplay::Shooting,
+    },
 };
 
-use super::assets::PlayerAssets;
+use super::{Player, assets::PlayerAssets};
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<PlayerAnimations>();
+    app.add_observer(setup_player_animations);
     app.add_systems(
         Update,
         play_animations.in_set(PostPhysicsAppSystems::PlayAnimations),
@@ -31,40 +36,47 @@ pub(crate) struct PlayerAnimations {
     shoot: AnimationNodeIndex,
 }
 
+/// Sets up the animation graph of each animation player that belongs to the [`Player`],
+/// i.e. the one of each weapon's view model.
 #[cfg_attr(feature = "hot_patch", hot)]
-pub(crate) fn setup_player_animations(
-    trigger: Trigger<OnAdd, AnimationPlayers>,
-    q_anim_players: Query<&AnimationPlayers>,
+fn setup_player_animations(
+    trigger: Trigger<OnInsert, AnimationPlayerOf>,
+    q_anim_player_of: Query<&AnimationPlayerOf>,
+    player: Query<(), With<Player>>,
     mut commands: Commands,
     assets: Res<PlayerAssets>,
     mut graphs: ResMut<Assets<AnimationGraph>>,
 ) {
-    let anim_players = q_anim_players.get(trigger.target()).unwrap();
-    for anim_player in anim_players.iter() {
-        let (graph, indices) = AnimationGraph::from_clips([
-            assets.hidden_animation.clone(),
-            assets.idle_animation.clone(),
-            assets.shoot_animation.clone(),
-            assets.walk_animation.clone(),
-        ]);
-        let [hidden_index, idle_index, shoot_index, walk_index] = indices.as_slice() else {
-            unreachable!()
-        };
-        let graph_handle = graphs.add(graph);
-
-        let animations = PlayerAnimations {
-            hidden: *hidden_index,
-            idle: *idle_index,
-            shoot: *shoot_index,
-            walk: *walk_index,
-        };
-        let transitions = AnimationTransitions::new();
-        commands.entity(anim_player).insert((
-            animations,
-            AnimationGraphHandle(graph_handle),
-            transitions,
-        ));
+    let anim_player = trigger.target();
+    let Ok(anim_player_of) = q_anim_player_of.get(anim_player) else {
+        return;
+    };
+    if !player.contains(**anim_player_of) {
+        return;
     }
+    let (graph, indices) = AnimationGraph::from_clips([
+        assets.hidden_animation.clone(),
+        assets.idle_animation.clone(),
+        assets.shoot_animation.clone(),
+        assets.walk_animation.clone(),
+    ]);
+    let [hidden_index, idle_index, shoot_index, walk_index] = indices.as_slice() else {
+        unreachable!()
+    };
+    let graph_handle = graphs.add(graph);
+
+    let animations = PlayerAnimations {
+        hidden: *hidden_index,
+        idle: *idle_index,
+        shoot: *shoot_index,
+        walk: *walk_index,
+    };
+    let transitions = AnimationTransitions::new();
+    commands.entity(anim_player).insert((
+        animations,
+        AnimationGraphHandle(graph_handle),
+        transitions,
+    ));
 }
 
 /// Managed by [`play_animations`]
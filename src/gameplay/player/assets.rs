@@ -22,6 +22,8 @@ pub(crate) struct PlayerAssets {
     #[dependency]
     pub(crate) throw_sound: Handle<AudioSource>,
     #[dependency]
+    pub(crate) headshot_sound: Handle<AudioSource>,
+    #[dependency]
     pub(crate) steps: ShuffleBag<Handle<AudioSource>>,
     #[dependency]
     pub(crate) jump_grunts: ShuffleBag<Handle<AudioSource>>,
@@ -30,10 +32,6 @@ pub(crate) struct PlayerAssets {
     #[dependency]
     pub(crate) jump_start_sounds: ShuffleBag<Handle<AudioSource>>,
     #[dependency]
-    pub(crate) shooting_sounds: ShuffleBag<Handle<AudioSource>>,
-    #[dependency]
-    pub(crate) reload_sound: Handle<AudioSource>,
-    #[dependency]
     pub(crate) hidden_animation: Handle<AnimationClip>,
     #[dependency]
     pub(crate) idle_animation: Handle<AnimationClip>,
@@ -52,6 +50,7 @@ impl FromWorld for PlayerAssets {
         Self {
             _model: assets.load(Player::scene_path()),
             throw_sound: assets.load("audio/sound_effects/throw.ogg"),
+            headshot_sound: assets.load("audio/sound_effects/impact/Impact08.ogg"),
             steps: ShuffleBag::try_new(
                 [
                     assets.load("audio/sound_effects/step/Footsteps_Rock_Walk_01.ogg"),
@@ -101,17 +100,6 @@ impl FromWorld for PlayerAssets {
                 rng,
             )
             .unwrap(),
-            shooting_sounds: ShuffleBag::try_new(
-                [
-                    assets.load("audio/sound_effects/shoot/Shotgun_Shot-001.ogg"),
-                    assets.load("audio/sound_effects/shoot/Shotgun_Shot-002.ogg"),
-                    assets.load("audio/sound_effects/shoot/Shotgun_Shot-003.ogg"),
-                    assets.load("audio/sound_effects/shoot/Shotgun_Shot-004.ogg"),
-                ],
-                rng,
-            )
-            .unwrap(),
-            reload_sound: assets.load("audio/sound_effects/shoot/Shotgun_Pump.ogg"),
             hurt_sounds: ShuffleBag::try_new(
                 [
                     assets.load("audio/sound_effects/hurt/damage_1_meghan.ogg"),
@@ -27,12 +27,15 @@ use bevy_simple_subsecond_system::hot;
 use crate::{
     CameraOrder, PostPhysicsAppSystems, RenderLayer,
     gameplay::{
-        animation::{AnimationPlayerAncestor, AnimationPlayerOf, AnimationPlayers},
+        animation::{AnimationPlayerAncestor, AnimationPlayerOf},
         level::LevelAssets,
-        player::camera_shake::{CameraShake, NonTraumaTransform},
+        player::{
+            camera_shake::{CameraShake, NonTraumaTransform},
+            weapons::{Weapon, WeaponViewModel, assets::WeaponAssets},
+        },
+        replay::Replay,
     },
     screens::{Screen, loading::LoadingScreen},
-    third_party::bevy_trenchbroom::LoadTrenchbroomModel as _,
 };
 
 use super::{PLAYER_FLOAT_HEIGHT, Player, default_input::Rotate};
@@ -43,6 +46,7 @@ pub(super) fn plugin(app: &mut App) {
     app.init_resource::<MouseInversion>();
 
     app.add_observer(spawn_view_model);
+    app.add_observer(move_anim_players_relationship_to_player);
     app.add_observer(add_render_layers_to_point_light);
     app.add_observer(add_render_layers_to_spot_light);
     app.add_observer(add_render_layers_to_directional_light);
@@ -80,7 +84,7 @@ fn spawn_view_model(
     trigger: Trigger<OnAdd, Player>,
     player_transform: Query<&Transform>,
     mut commands: Commands,
-    assets: Res<AssetServer>,
+    weapon_assets: Res<WeaponAssets>,
     level_assets: Res<LevelAssets>,
     fov: Res<WorldModelFov>,
 ) {
@@ -168,32 +172,43 @@ fn spawn_view_model(
                 env_map,
             ));
 
-            // Spawn the player's view model
-            parent
-                .spawn((
-                    Name::new("View Model"),
-                    SceneRoot(assets.load_trenchbroom_model::<Player>()),
-                ))
-                .observe(configure_player_view_model);
-        })
-        .observe(move_anim_players_relationship_to_player);
+            // Spawn the view model of every weapon. Only the selected one is visible.
+            for weapon in Weapon::ALL {
+                parent
+                    .spawn((
+                        Name::new(format!("View Model ({})", weapon.definition().name)),
+                        WeaponViewModel(weapon),
+                        weapon.definition().view_model_transform,
+                        SceneRoot(weapon_assets.get(weapon).view_model.clone()),
+                        Visibility::Hidden,
+                    ))
+                    .observe(configure_player_view_model);
+            }
+        });
 }
 
 /// It makes more sense for the animation players to be related to the [`Player`] entity
 /// than to the [`PlayerCamera`] entity, so let's move the relationship there.
+///
+/// This reacts to every animation player on its own, as the view models of the weapons finish loading one by one.
 #[cfg_attr(feature = "hot_patch", hot)]
 fn move_anim_players_relationship_to_player(
-    trigger: Trigger<OnAdd, AnimationPlayers>,
-    q_anim_player: Query<&AnimationPlayers>,
+    trigger: Trigger<OnInsert, AnimationPlayerOf>,
+    q_anim_player_of: Query<&AnimationPlayerOf>,
+    player_camera: Query<(), With<PlayerCamera>>,
     player: Single<Entity, With<Player>>,
     mut commands: Commands,
 ) {
-    let anim_players = q_anim_player.get(trigger.target()).unwrap();
-    for anim_player in anim_players.iter() {
-        commands
-            .entity(anim_player)
-            .insert(AnimationPlayerOf(*player));
+    let anim_player = trigger.target();
+    let Ok(anim_player_of) = q_anim_player_of.get(anim_player) else {
+        return;
+    };
+    if !player_camera.contains(**anim_player_of) {
+        return;
     }
+    commands
+        .entity(anim_player)
+        .insert(AnimationPlayerOf(*player));
 }
 
 #[cfg_attr(feature = "hot_patch", hot)]
@@ -202,8 +217,14 @@ fn configure_player_view_model(
     mut commands: Commands,
     q_children: Query<&Children>,
     q_mesh: Query<(), With<Mesh3d>>,
+    q_view_model: Query<&WeaponViewModel>,
+    q_material: Query<&MeshMaterial3d<StandardMaterial>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
     let view_model = trigger.target();
+    let tint = q_view_model
+        .get(view_model)
+        .map_or(Color::WHITE, |view_model| view_model.0.definition().tint);
 
     for child in iter::once(view_model)
         .chain(q_children.iter_descendants(view_model))
@@ -215,6 +236,30 @@ fn configure_player_view_model(
             // The arm is free-floating, so shadows would look weird.
             NotShadowCaster,
         ));
+
+        // Weapons sharing a model get their own copy of its materials to tint.
+        if tint == Color::WHITE {
+            continue;
+        }
+        let Some(material) = q_material
+            .get(child)
+            .ok()
+            .and_then(|material| materials.get(&material.0))
+        else {
+            continue;
+        };
+        let mut material = material.clone();
+        let (base, factor) = (material.base_color.to_linear(), tint.to_linear());
+        material.base_color = LinearRgba::new(
+            base.red * factor.red,
+            base.green * factor.green,
+            base.blue * factor.blue,
+            base.alpha,
+        )
+        .into();
+        commands
+            .entity(child)
+            .insert(MeshMaterial3d(materials.add(material)));
     }
 }
 
@@ -225,6 +270,7 @@ fn rotate_camera_yaw_and_pitch(
     sensitivity: Res<CameraSensitivity>,
     window: Single<&Window>,
     mouse_inversion: Res<MouseInversion>,
+    replay: Option<Res<Replay>>,
 ) {
     if window.cursor_options.grab_mode == CursorGrabMode::None {
         return;
@@ -236,19 +282,12 @@ fn rotate_camera_yaw_and_pitch(
         return;
     }
 
-    // Note that we are not multiplying by delta_time here.
-    // The reason is that for mouse movement, we already get the full movement that happened since the last frame.
-    // This means that if we multiply by delta_time, we will get a smaller rotation than intended by the user.
-    // This situation is reversed when reading e.g. analog input from a gamepad however, where the same rules
-    // as for keyboard input apply. Such an input should be multiplied by delta_time to get the intended rotation
-    // independent of the framerate.
-    let pitch_sign = if mouse_inversion.invert_mouse_y {
-        -1.0
+    // Replays record the rotation after scaling it, so that they do not depend on the viewer's settings.
+    let (delta_yaw, delta_pitch) = if replay.is_some() {
+        delta.into()
     } else {
-        1.0
+        scale_rotation(delta, &sensitivity, &mouse_inversion).into()
     };
-    let delta_yaw = delta.x * sensitivity.x;
-    let delta_pitch = delta.y * sensitivity.y * pitch_sign;
 
     let (yaw, pitch, roll) = transform.rotation.to_euler(EulerRot::YXZ);
     let yaw = yaw + delta_yaw;
@@ -265,6 +304,29 @@ fn rotate_camera_yaw_and_pitch(
     transform.rotation = Quat::from_euler(EulerRot::YXZ, yaw, pitch, roll);
 }
 
+/// Scales a [`Rotate`] value by the camera settings into the yaw and pitch to rotate the camera by.
+pub(crate) fn scale_rotation(
+    delta: Vec2,
+    sensitivity: &CameraSensitivity,
+    mouse_inversion: &MouseInversion,
+) -> Vec2 {
+    // Note that we are not multiplying by delta_time here.
+    // The reason is that for mouse movement, we already get the full movement that happened since the last frame.
+    // This means that if we multiply by delta_time, we will get a smaller rotation than intended by the user.
+    // This situation is reversed when reading e.g. analog input from a gamepad however, where the same rules
+    // as for keyboard input apply. Such an input should be multiplied by delta_time to get the intended rotation
+    // independent of the framerate.
+    let pitch_sign = if mouse_inversion.invert_mouse_y {
+        -1.0
+    } else {
+        1.0
+    };
+    Vec2::new(
+        delta.x * sensitivity.x,
+        delta.y * sensitivity.y * pitch_sign,
+    )
+}
+
 #[cfg_attr(feature = "hot_patch", hot)]
 fn sync_camera_translation_with_player(
     mut player_camera_parent: Single<&mut NonTraumaTransform, With<PlayerCamera>>,
@@ -50,10 +50,28 @@ pub(crate) struct DropProp;
 #[input_action(output = bool)]
 pub(crate) struct Shoot;
 
+#[derive(Debug, InputAction)]
+#[input_action(output = bool)]
+pub(crate) struct Reload;
+
+#[derive(Debug, InputAction)]
+#[input_action(output = bool)]
+pub(crate) struct ThrowGrenade;
+
 #[derive(Debug, InputAction)]
 #[input_action(output = bool)]
 pub(crate) struct OpenUpgradeMenu;
 
+/// Selects a weapon by its slot. The value is the number of the slot, starting at 1.
+#[derive(Debug, InputAction)]
+#[input_action(output = f32)]
+pub(crate) struct SelectWeapon;
+
+/// Switches to the next weapon if positive, or to the previous one if negative.
+#[derive(Debug, InputAction)]
+#[input_action(output = f32)]
+pub(crate) struct CycleWeapon;
+
 #[derive(Debug, InputContext, Default)]
 pub(crate) struct DefaultInputContext;
 
@@ -100,13 +118,47 @@ fn default_binding(
         .bind::<PickupProp>()
         .to((KeyCode::KeyE, GamepadButton::East));
 
+    // Throws the held prop.
     actions
         .bind::<DropProp>()
-        .to((KeyCode::KeyG, GamepadButton::East));
+        .to((KeyCode::KeyG, GamepadButton::RightTrigger));
 
     actions.bind::<Shoot>().to(MouseButton::Left);
 
+    actions
+        .bind::<Reload>()
+        .to((KeyCode::KeyR, GamepadButton::West));
+
+    actions
+        .bind::<ThrowGrenade>()
+        .to((KeyCode::KeyQ, GamepadButton::LeftTrigger));
+
     actions.bind::<OpenUpgradeMenu>().to(KeyCode::KeyF);
+
+    const WEAPON_SLOT_KEYS: [KeyCode; 9] = [
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+        KeyCode::Digit5,
+        KeyCode::Digit6,
+        KeyCode::Digit7,
+        KeyCode::Digit8,
+        KeyCode::Digit9,
+    ];
+    for (index, key) in WEAPON_SLOT_KEYS.into_iter().enumerate() {
+        actions
+            .bind::<SelectWeapon>()
+            .to(key.with_modifiers(Scale::splat(index as f32 + 1.0)));
+    }
+
+    // Scrolling down selects the next weapon.
+    actions.bind::<CycleWeapon>().to((
+        Input::mouse_wheel().with_modifiers((SwizzleAxis::YXZ, Negate::all())),
+        GamepadButton::North,
+        GamepadButton::DPadRight,
+        GamepadButton::DPadLeft.with_modifiers(Negate::all()),
+    ));
 }
 
 #[derive(Resource, Default, Reflect, Deref, DerefMut)]
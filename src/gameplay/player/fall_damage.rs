@@ -3,7 +3,7 @@ use bevy::prelude::*;
 
 use crate::{
     gameplay::{
-        health::{Health, OnDamage},
+        health::{DamageKind, Health, OnDamage},
         player::{GroundCast, Player},
     },
     screens::Screen,
@@ -45,7 +45,7 @@ fn apply_fall_damage(
     let max_damage = health.max / 4.0;
     let damage = (1.5 * (*last_y_speed - FALL_DAMAGE_THRESHOLD)).min(max_damage);
 
-    commands.trigger_targets(OnDamage(damage), entity);
+    commands.trigger_targets(OnDamage::new(damage, DamageKind::Fall), entity);
 
     *last_y_speed = velocity.y.abs();
 }
@@ -0,0 +1,155 @@
+//! Grenades, the player's secondary equipment.
+//!
+//! A thrown grenade bounces around until its fuse runs out, or explodes right away when it hits an enemy.
+//! The explosion is a regular [`Explosive`], so grenades can set off barrels and start chain reactions.
+//! The player carries a limited number of [`Grenades`], which are refilled whenever a new wave starts preparing.
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+#[cfg(feature = "hot_patch")]
+use bevy_simple_subsecond_system::hot;
+
+use crate::{
+    auto_timer::{AutoTimer, OnAutoTimerFinish},
+    gameplay::{
+        explosion::{ExplodeOnContact, Explosive, OnExplode, effects::PropExplosionVfx},
+        player::{Player, camera::PlayerCamera, default_input::ThrowGrenade, pickup::HeldProp},
+        waves::WaveStartedPreparing,
+    },
+    screens::Screen,
+    third_party::avian3d::CollisionLayer,
+};
+
+/// How many grenades the player can carry at the start of a run.
+const STARTING_CAPACITY: u32 = 2;
+/// How long it takes for a grenade to explode after being thrown, in seconds.
+const FUSE_SECONDS: f32 = 2.0;
+const THROW_SPEED: f32 = 16.0;
+/// How much the throw is angled upwards, so grenades fly in an arc.
+const THROW_LIFT: f32 = 3.0;
+const GRENADE_RADIUS: f32 = 0.12;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(Grenades, Grenade)>();
+    app.init_resource::<GrenadeAssets>();
+    app.add_observer(setup_grenades);
+    app.add_observer(throw_grenade);
+    app.add_observer(refill_grenades);
+}
+
+/// The grenades the player carries.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub(crate) struct Grenades {
+    pub(crate) count: u32,
+    /// The most grenades the player can carry.
+    pub(crate) capacity: u32,
+}
+
+impl Grenades {
+    pub(crate) fn refill(&mut self) {
+        self.count = self.capacity;
+    }
+}
+
+/// A thrown grenade.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub(crate) struct Grenade;
+
+#[derive(Resource)]
+struct GrenadeAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+impl FromWorld for GrenadeAssets {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            mesh: world.add_asset(Sphere::new(GRENADE_RADIUS).mesh().ico(2).unwrap()),
+            material: world.add_asset(StandardMaterial {
+                base_color: Color::srgb(0.2, 0.25, 0.15),
+                perceptual_roughness: 0.6,
+                metallic: 0.4,
+                ..default()
+            }),
+        }
+    }
+}
+
+fn setup_grenades(trigger: Trigger<OnAdd, Player>, mut commands: Commands) {
+    commands.entity(trigger.target()).insert(Grenades {
+        count: STARTING_CAPACITY,
+        capacity: STARTING_CAPACITY,
+    });
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn throw_grenade(
+    trigger: Trigger<Started<ThrowGrenade>>,
+    mut player: Query<(&mut Grenades, &LinearVelocity, Has<HeldProp>)>,
+    camera: Single<&Transform, With<PlayerCamera>>,
+    grenade_assets: Res<GrenadeAssets>,
+    mut commands: Commands,
+) {
+    let Ok((mut grenades, player_velocity, holding_prop)) = player.get_mut(trigger.target()) else {
+        return;
+    };
+    if grenades.count == 0 || holding_prop {
+        return;
+    }
+    grenades.count -= 1;
+
+    let velocity = camera.forward() * THROW_SPEED + Vec3::Y * THROW_LIFT + player_velocity.0;
+    commands
+        .spawn((
+            Name::new("Grenade"),
+            Grenade,
+            Transform::from_translation(camera.translation + camera.forward() * 0.5),
+            Mesh3d(grenade_assets.mesh.clone()),
+            MeshMaterial3d(grenade_assets.material.clone()),
+            RigidBody::Dynamic,
+            Collider::sphere(GRENADE_RADIUS),
+            // Grenades pass through the player so they can be thrown while running forward.
+            CollisionLayers::new(
+                CollisionLayer::Prop,
+                [
+                    CollisionLayer::Default,
+                    CollisionLayer::Prop,
+                    CollisionLayer::Npc,
+                ],
+            ),
+            Restitution::new(0.4),
+            LinearVelocity(velocity),
+            Explosive {
+                radius: 5.0,
+                inner_radius: 1.5,
+                impulse_strength: 30.0,
+                damage: 150.0,
+                ..default()
+            },
+            ExplodeOnContact {
+                layers: CollisionLayer::Npc.into(),
+            },
+            PropExplosionVfx,
+            AutoTimer(Timer::from_seconds(FUSE_SECONDS, TimerMode::Once)),
+            StateScoped(Screen::Gameplay),
+        ))
+        .observe(
+            |trigger: Trigger<OnAutoTimerFinish>, mut commands: Commands| {
+                commands
+                    .entity(trigger.target())
+                    .trigger(OnExplode::default());
+            },
+        );
+}
+
+fn refill_grenades(
+    _trigger: Trigger<WaveStartedPreparing>,
+    mut grenades: Query<&mut Grenades, With<Player>>,
+) {
+    for mut grenades in &mut grenades {
+        grenades.refill();
+    }
+}
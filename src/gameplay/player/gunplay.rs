@@ -1,15 +1,30 @@
 use std::time::Duration;
 
-use super::{Player, assets::PlayerAssets, camera::PlayerCamera, default_input::Shoot};
+use super::{
+    Player,
+    assets::PlayerAssets,
+    camera::PlayerCamera,
+    default_input::Shoot,
+    pickup::HeldProp,
+    weapons::{
+        WeaponInventory,
+        ammo::{Reloading, start_reload},
+        assets::WeaponAssets,
+    },
+};
 use crate::{
     RenderLayer,
-    audio::{sound_effect, sped_up_sound_effect},
+    audio::{SoundEffect, sound_effect, sped_up_sound_effect},
     despawn_after::DespawnAfter,
     gameplay::{
         crosshair::CrosshairState,
-        health::OnDamage,
-        npc::Npc,
+        health::{DamageKind, OnDamage},
+        npc::{
+            hit_zones::{HitZone, LastHit},
+            stats::NpcStats,
+        },
         player::{GroundCast, camera::CustomRenderLayer, camera_shake::OnTrauma},
+        rng::{GameplayRng, RngStream},
     },
     screens::Screen,
     third_party::avian3d::CollisionLayer,
@@ -25,35 +40,78 @@ use bevy_simple_subsecond_system::hot;
 #[reflect(Component)]
 pub(crate) struct Shooting;
 
+/// A global event that is triggered when a shot hits an NPC in the head.
+#[derive(Event, Debug)]
+pub(crate) struct OnHeadshot;
+
+/// The weapon is being cycled after a shot and cannot fire until the timer finishes.
 #[derive(Component, Debug, Reflect)]
 #[reflect(Component)]
-pub(crate) struct Reloading;
+pub(crate) struct FireCooldown {
+    timer: Timer,
+    /// Whether the sound of cycling the weapon was played.
+    cycled: bool,
+}
 
-#[derive(Debug, Component, Reflect)]
-#[reflect(Component)]
+/// How far into the [`FireCooldown`] the sound of cycling the weapon is played.
+/// It overlaps a little with the shooting sound.
+const CYCLE_SOUND_FRACTION: f32 = 0.3;
+
+/// How far shots reach.
+const MAX_SHOT_DISTANCE: f32 = 300.0;
+/// Pellets pass through props that are thinner than this, like planks and chairs.
+const THIN_PROP_THICKNESS: f32 = 0.1;
+/// How much damage pellets keep after passing through a thin prop.
+const THIN_PROP_DAMAGE_FACTOR: f32 = 0.6;
+
+/// The stats of a weapon in the player's [`WeaponInventory`].
+#[derive(Debug, Clone, Reflect)]
 pub(crate) struct WeaponStats {
+    /// The damage of a single pellet at close range.
     pub(crate) damage: f32,
+    /// The distance up to which pellets deal full damage.
+    pub(crate) falloff_start: f32,
+    /// The distance from which pellets only deal [`min_damage_fraction`](Self::min_damage_fraction) of their damage.
+    /// The damage decreases linearly between the start and end.
+    pub(crate) falloff_end: f32,
+    pub(crate) min_damage_fraction: f32,
+    /// How many NPCs a pellet can pass through after hitting the first one.
+    pub(crate) penetration: u32,
+    /// How much damage a pellet keeps each time it passes through an NPC.
+    pub(crate) penetration_damage_factor: f32,
     pub(crate) pellets: u32,
     pub(crate) spread_radius: f32,
     pub(crate) pushback: f32,
     pub(crate) extra_enemy_explosion_radius: f32,
+    /// Shots per second.
+    pub(crate) fire_rate: f32,
+    /// Shells loaded per second.
+    pub(crate) reload_rate: f32,
+    pub(crate) magazine_size: u32,
+    /// The most shells that can be carried in reserve.
+    pub(crate) reserve_size: u32,
 }
 
-pub(super) fn plugin(app: &mut App) {
-    // Only until there is a weapon pickup/selection system
-    app.add_observer(setup_weapon_stats);
+impl WeaponStats {
+    /// The damage of a pellet that hits something at the given distance.
+    pub(crate) fn damage_at(&self, distance: f32) -> f32 {
+        let range = (self.falloff_end - self.falloff_start).max(f32::EPSILON);
+        let t = ((distance - self.falloff_start) / range).clamp(0.0, 1.0);
+        self.damage * 1.0_f32.lerp(self.min_damage_fraction, t)
+    }
+}
 
+pub(super) fn plugin(app: &mut App) {
     app.add_observer(shooting);
     app.add_observer(shooting_sounds);
     app.add_observer(handle_hits);
-    app.add_observer(shooting_sounds_reload);
     app.add_observer(spawn_muzzle_flash);
     app.add_observer(shot_pushback);
     app.add_observer(lock_on_shoot);
+    app.add_observer(headshot_feedback);
 
-    // Only until the animations work again.
-    app.add_systems(Update, remove_shooting);
-    app.add_systems(Update, trigger_reload_sound);
+    app.register_type::<FireCooldown>();
+    app.add_systems(Update, tick_fire_cooldown);
     app.add_systems(
         Update,
         (
@@ -65,100 +123,106 @@ pub(super) fn plugin(app: &mut App) {
     app.init_resource::<BulletImpact>();
 }
 
-fn setup_weapon_stats(trigger: Trigger<OnAdd, Player>, mut commands: Commands) {
-    commands.entity(trigger.target()).insert(WeaponStats {
-        damage: 5.0,
-        pellets: 16,
-        spread_radius: 0.15,
-        pushback: 12.0,
-        extra_enemy_explosion_radius: 0.0,
-    });
-}
-
 fn shooting(
     trigger: Trigger<Fired<Shoot>>,
     mut commands: Commands,
-    shooting: Query<(), With<Shooting>>,
-    crosshair_state: Single<&CrosshairState>,
+    mut player: Query<(
+        &mut WeaponInventory,
+        Has<FireCooldown>,
+        Has<Reloading>,
+        Has<HeldProp>,
+    )>,
+    crosshair_state: Option<Single<&CrosshairState>>,
 ) {
     let entity = trigger.target();
+    let Ok((mut inventory, cooling_down, reloading, holding_prop)) = player.get_mut(entity) else {
+        return;
+    };
 
-    if shooting.contains(entity) || !crosshair_state.wants_invisible.is_empty() {
+    // The crosshair is hidden while a menu is open.
+    let crosshair_hidden =
+        crosshair_state.is_some_and(|crosshair_state| !crosshair_state.wants_invisible.is_empty());
+    // Both hands are busy while holding a prop.
+    if cooling_down || crosshair_hidden || holding_prop {
         return;
     }
 
-    commands.entity(entity).insert(Shooting);
-    commands.trigger(OnTrauma(0.4));
-}
-
-fn remove_shooting(
-    shooting: Single<Entity, (With<Shooting>, With<Reloading>)>,
-    time: Res<Time>,
-    mut timer: Local<Option<Timer>>,
-    mut commands: Commands,
-) {
-    let reload_time = 375;
-    let timer = timer.get_or_insert_with(|| {
-        Timer::new(Duration::from_millis(reload_time), TimerMode::Repeating)
-    });
-    timer.tick(time.delta());
-    if !timer.finished() {
+    let slot = inventory.selected_mut();
+    if slot.ammo.loaded == 0 {
+        if !reloading {
+            start_reload(&mut commands, entity, slot);
+        }
         return;
     }
-
-    commands.entity(*shooting).remove::<Shooting>();
-    commands.entity(*shooting).remove::<Reloading>();
+    slot.ammo.loaded -= 1;
+
+    // Firing interrupts reloading.
+    commands.entity(entity).remove::<Reloading>().insert((
+        Shooting,
+        FireCooldown {
+            timer: Timer::from_seconds(1.0 / slot.stats.fire_rate, TimerMode::Once),
+            cycled: false,
+        },
+    ));
+    commands.trigger(OnTrauma(0.4));
 }
 
-fn trigger_reload_sound(
-    shooting: Single<Entity, With<Shooting>>,
+fn tick_fire_cooldown(
+    mut player: Query<(Entity, &mut FireCooldown, &WeaponInventory)>,
     time: Res<Time>,
-    mut timer: Local<Option<Timer>>,
+    weapon_assets: Res<WeaponAssets>,
+    state: Res<State<Screen>>,
     mut commands: Commands,
 ) {
-    // The name is not precise, we simply start the reload time after this time of the shooting sound (they overlap a little)
-    let shooting_sound_len = 175;
-    let timer = timer.get_or_insert_with(|| {
-        Timer::new(
-            Duration::from_millis(shooting_sound_len),
-            TimerMode::Repeating,
-        )
-    });
-    timer.tick(time.delta());
-    if !timer.finished() {
-        return;
-    }
+    for (entity, mut cooldown, inventory) in &mut player {
+        cooldown.timer.tick(time.delta());
+        let slot = inventory.selected();
+        if !cooldown.cycled && cooldown.timer.fraction() >= CYCLE_SOUND_FRACTION {
+            cooldown.cycled = true;
+            if *state == Screen::Gameplay {
+                let sound = weapon_assets.get(slot.weapon).reload_sound.clone();
+                commands.spawn(sound_effect(sound));
+            }
+        }
+        if !cooldown.timer.finished() {
+            continue;
+        }
 
-    commands.entity(*shooting).insert(Reloading);
+        // The shooting animation usually removes `Shooting` earlier.
+        commands.entity(entity).remove::<(FireCooldown, Shooting)>();
+        if slot.ammo.loaded == 0 {
+            start_reload(&mut commands, entity, slot);
+        }
+    }
 }
 
 fn shooting_sounds(
-    _trigger: Trigger<OnAdd, Shooting>,
+    trigger: Trigger<OnAdd, Shooting>,
     mut commands: Commands,
-    mut player_assets: ResMut<PlayerAssets>,
+    inventory: Query<&WeaponInventory>,
+    mut weapon_assets: ResMut<WeaponAssets>,
     state: Res<State<Screen>>,
 ) {
     if *state != Screen::Gameplay {
         return;
     }
-
-    let rng = &mut rand::thread_rng();
-    let shooting_sound = player_assets.shooting_sounds.pick(rng).clone();
-
-    commands.spawn(sound_effect(shooting_sound));
-}
-
-fn shooting_sounds_reload(
-    _trigger: Trigger<OnAdd, Reloading>,
-    mut commands: Commands,
-    player_assets: ResMut<PlayerAssets>,
-    state: Res<State<Screen>>,
-) {
-    if *state != Screen::Gameplay {
+    let Ok(inventory) = inventory.get(trigger.target()) else {
         return;
-    }
+    };
 
-    commands.spawn(sound_effect(player_assets.reload_sound.clone()));
+    let weapon = inventory.selected().weapon;
+    let rng = &mut rand::thread_rng();
+    let shooting_sound = weapon_assets
+        .get_mut(weapon)
+        .shooting_sounds
+        .pick(rng)
+        .clone();
+
+    commands.spawn((
+        AudioPlayer(shooting_sound),
+        PlaybackSettings::DESPAWN.with_speed(weapon.definition().sound_speed),
+        SoundEffect,
+    ));
 }
 
 #[cfg_attr(feature = "hot_patch", hot)]
@@ -197,12 +261,13 @@ fn spawn_muzzle_flash(
 
 fn shot_pushback(
     trigger: Trigger<OnAdd, Shooting>,
-    mut player: Query<(&mut LinearVelocity, &GroundCast, &WeaponStats), With<Player>>,
+    mut player: Query<(&mut LinearVelocity, &GroundCast, &WeaponInventory), With<Player>>,
     player_camera_parent: Single<&Transform, With<PlayerCamera>>,
 ) {
-    let Ok((mut lin_vel, ground_cast, weapon_stats)) = player.get_mut(trigger.target()) else {
+    let Ok((mut lin_vel, ground_cast, inventory)) = player.get_mut(trigger.target()) else {
         return;
     };
+    let weapon_stats = inventory.stats();
     let back = player_camera_parent.back();
 
     if ground_cast.is_none() {
@@ -216,15 +281,20 @@ fn handle_hits(
     spatial_query: SpatialQuery,
     player_camera_parent: Single<&Transform, With<PlayerCamera>>,
     collider_of: Query<&ColliderOf>,
-    weapon_stats: Single<&WeaponStats, With<Player>>,
-    player: Single<Entity, With<Player>>,
+    colliders: Query<(&Collider, &GlobalTransform, Option<&CollisionLayers>)>,
+    body_colliders: Query<&RigidBodyColliders>,
+    player: Single<(Entity, &WeaponInventory), With<Player>>,
     bullet_impact: Res<BulletImpact>,
     mut commands: Commands,
-    npcs: Query<(), With<Npc>>,
+    hit_zones: Query<&HitZone>,
+    npcs: Query<(&NpcStats, &GlobalTransform)>,
     mut player_assets: ResMut<PlayerAssets>,
+    mut rng: ResMut<GameplayRng>,
     state: Res<State<Screen>>,
 ) {
-    let mut rng = &mut rand::thread_rng();
+    let (player, inventory) = player.into_inner();
+    let weapon_stats = inventory.stats();
+    let rng = rng.stream(RngStream::Gunplay);
 
     // Ray origin and base direction
     let origin = player_camera_parent.translation;
@@ -233,65 +303,167 @@ fn handle_hits(
     let right = player_camera_parent.right();
     let up = player_camera_parent.up();
 
+    let mut headshot = false;
     for _i in 1..=weapon_stats.pellets {
         // Sample random point within a circle for spread
-        let point = Circle::new(weapon_stats.spread_radius).sample_interior(&mut rng);
+        let point = Circle::new(weapon_stats.spread_radius).sample_interior(rng);
 
         // Apply spread to the direction
         let spread_vec = base_direction.as_vec3() + right * point.x + up * point.y;
         let spread_direction = Dir3::new(spread_vec).unwrap_or(Dir3::NEG_Z);
 
-        // Configuration for the ray cast
-        let max_distance = 300.0;
+        // Configuration for the ray cast.
+        // Gibs are not in the mask, so pellets always fly through them.
         let solid = true;
-        let filter = SpatialQueryFilter::default()
+        let mut filter = SpatialQueryFilter::default()
             .with_mask([
+                CollisionLayer::HitZone,
                 CollisionLayer::Npc,
                 CollisionLayer::Prop,
                 CollisionLayer::Default,
             ])
-            .with_excluded_entities([*player]);
-
-        // Cast ray with spread and handle first hit
-        let Some(first_hit) =
-            spatial_query.cast_ray(origin, spread_direction, max_distance, solid, &filter)
-        else {
-            continue;
-        };
-        let bias = 0.1;
-        commands.spawn((
-            Name::new("bullet impact particles"),
-            DespawnAfter::new(Duration::from_secs(2)),
-            particle_bundle(&bullet_impact),
-            Transform::from_translation(
-                origin + spread_direction * (first_hit.distance - bias).max(0.0),
-            ),
-        ));
+            .with_excluded_entities([player]);
+
+        // Follow the pellet through everything it penetrates.
+        let mut traveled = 0.0;
+        let mut damage_factor = 1.0;
+        let mut penetrations_left = weapon_stats.penetration;
+        while let Some(hit) = spatial_query.cast_ray(
+            origin + spread_direction * traveled,
+            spread_direction,
+            MAX_SHOT_DISTANCE - traveled,
+            solid,
+            &filter,
+        ) {
+            traveled += hit.distance;
+            let hit_point = origin + spread_direction * traveled;
+            let bias = 0.1;
+            commands.spawn((
+                Name::new("bullet impact particles"),
+                DespawnAfter::new(Duration::from_secs(2)),
+                particle_bundle(&bullet_impact),
+                Transform::from_translation(hit_point - spread_direction * bias),
+            ));
+
+            let Ok(&ColliderOf { body }) = collider_of.get(hit.entity) else {
+                error!("Hit something without a rigid body");
+                break;
+            };
+
+            let mut hit_zone = hit_zones.get(hit.entity).ok().copied();
+            if hit_zone.is_none()
+                && let Ok((stats, _)) = npcs.get(body)
+            {
+                // The capsule of an NPC is wider than its limbs, so look for a limb behind the point where the
+                // capsule was hit. If there is none, e.g. because the model has no hit zones, it counts as the torso.
+                let zone_filter = SpatialQueryFilter::from_mask(CollisionLayer::HitZone);
+                let limb = spatial_query
+                    .cast_ray(
+                        hit_point,
+                        spread_direction,
+                        2.0 * stats.radius(),
+                        solid,
+                        &zone_filter,
+                    )
+                    .filter(|limb| collider_of.get(limb.entity).is_ok_and(|c| c.body == body))
+                    .and_then(|limb| hit_zones.get(limb.entity).ok().copied());
+                hit_zone = Some(limb.unwrap_or(HitZone::Torso));
+            }
+            if hit_zone.is_some() {
+                // play jump sound sped up, sound like flesh impact
+                let rng = &mut rand::thread_rng();
+                let sound = player_assets.jump_start_sounds.pick(rng).clone();
+                if *state == Screen::Gameplay {
+                    commands.spawn(sped_up_sound_effect(sound.clone()));
+                }
+            } else {
+                // play throw sound sped up, sounds like wall impact
+                let sound = player_assets.throw_sound.clone();
+                if *state == Screen::Gameplay {
+                    commands.spawn(sped_up_sound_effect(sound.clone()));
+                }
+            }
 
-        if npcs.contains(first_hit.entity) {
-            // play jump sound sped up, sound like flesh impact
-            let rng = &mut rand::thread_rng();
-            let sound = player_assets.jump_start_sounds.pick(rng).clone();
-            if *state == Screen::Gameplay {
-                commands.spawn(sped_up_sound_effect(sound.clone()));
+            let mut damage = weapon_stats.damage_at(traveled) * damage_factor;
+            if let Some(zone) = hit_zone {
+                headshot |= zone == HitZone::Head;
+                if let Ok((stats, transform)) = npcs.get(body) {
+                    damage *= stats.hit_zones.get(zone);
+                    // Remember where the NPC was hit so that a killing blow can tear off the right limb.
+                    commands.entity(body).try_insert(LastHit {
+                        zone,
+                        offset: hit_point - transform.translation(),
+                    });
+                }
             }
-        } else {
-            // play throw sound sped up, sounds like wall impact
-            let sound = player_assets.throw_sound.clone();
-            if *state == Screen::Gameplay {
-                commands.spawn(sped_up_sound_effect(sound.clone()));
+            commands.entity(body).trigger(
+                OnDamage::new(damage, DamageKind::Bullet)
+                    .with_instigator(player)
+                    .with_position(hit_point),
+            );
+
+            // Decide whether the pellet keeps going.
+            let Ok((collider, collider_transform, layers)) = colliders.get(hit.entity) else {
+                break;
+            };
+            if hit_zone.is_some() && penetrations_left > 0 {
+                penetrations_left -= 1;
+                damage_factor *= weapon_stats.penetration_damage_factor;
+                // Don't hit the other limbs of the same NPC.
+                filter
+                    .excluded_entities
+                    .extend(body_colliders.get(body).into_iter().flat_map(|c| c.iter()));
+            } else if layers.is_some_and(|layers| layers.memberships.has_all(CollisionLayer::Prop))
+                && thickness(collider, collider_transform, hit_point, spread_direction)
+                    < THIN_PROP_THICKNESS
+            {
+                damage_factor *= THIN_PROP_DAMAGE_FACTOR;
+                filter.excluded_entities.insert(hit.entity);
+            } else {
+                break;
             }
         }
+    }
 
-        let Ok(ColliderOf { body }) = collider_of.get(first_hit.entity) else {
-            error!("Hit something without a rigid body");
-            continue;
-        };
+    if headshot {
+        commands.trigger(OnHeadshot);
+    }
+}
+
+/// How thick the collider is where a ray enters it, up to [`THIN_PROP_THICKNESS`].
+fn thickness(
+    collider: &Collider,
+    transform: &GlobalTransform,
+    entry_point: Vec3,
+    direction: Dir3,
+) -> f32 {
+    // Cast back from behind the collider to find where the ray would exit it.
+    // If the ray starts inside the collider, it hits immediately and the collider is too thick.
+    let behind = entry_point + direction * THIN_PROP_THICKNESS;
+    let (_, rotation, translation) = transform.to_scale_rotation_translation();
+    collider
+        .cast_ray(
+            translation,
+            rotation,
+            behind,
+            -direction.as_vec3(),
+            THIN_PROP_THICKNESS,
+            true,
+        )
+        .map_or(0.0, |(distance, _normal)| THIN_PROP_THICKNESS - distance)
+}
 
-        commands
-            .entity(*body)
-            .trigger(OnDamage(weapon_stats.damage));
+fn headshot_feedback(
+    _trigger: Trigger<OnHeadshot>,
+    player_assets: Res<PlayerAssets>,
+    state: Res<State<Screen>>,
+    mut commands: Commands,
+) {
+    if *state != Screen::Gameplay {
+        return;
     }
+    commands.spawn(sped_up_sound_effect(player_assets.headshot_sound.clone()));
+    commands.trigger(OnTrauma(0.2));
 }
 
 fn lock_on_shoot(_trigger: Trigger<OnAdd, Shooting>, mut commands: Commands) {
@@ -394,3 +566,25 @@ fn create_bullet_impact_asset() -> EffectAsset {
         .render(orientation)
         .render(round)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gameplay::player::weapons::Weapon;
+
+    #[test]
+    fn damage_falls_off_linearly() {
+        let stats = WeaponStats {
+            damage: 10.0,
+            falloff_start: 10.0,
+            falloff_end: 30.0,
+            min_damage_fraction: 0.2,
+            ..Weapon::PumpShotgun.definition().stats
+        };
+        assert_eq!(stats.damage_at(0.0), 10.0);
+        assert_eq!(stats.damage_at(10.0), 10.0);
+        assert!((stats.damage_at(20.0) - 6.0).abs() < 1e-5);
+        assert!((stats.damage_at(30.0) - 2.0).abs() < 1e-5);
+        assert!((stats.damage_at(300.0) - 2.0).abs() < 1e-5);
+    }
+}
@@ -10,9 +10,24 @@ use crate::{
 
 pub(super) fn plugin(app: &mut App) {
     app.add_observer(shake_on_hit);
+    app.add_observer(play_hurt_sound);
 }
 
 fn shake_on_hit(
+    trigger: Trigger<OnDamage>,
+    player: Query<(), With<Player>>,
+    mut commands: Commands,
+) {
+    if !player.contains(trigger.target()) {
+        return;
+    }
+
+    let base_trauma = 0.7 / 10.0;
+    let dmg = trigger.amount;
+    commands.trigger(OnTrauma(base_trauma * dmg));
+}
+
+fn play_hurt_sound(
     trigger: Trigger<OnDamage>,
     player: Query<&Health, With<Player>>,
     mut commands: Commands,
@@ -22,10 +37,6 @@ fn shake_on_hit(
         return;
     };
 
-    let base_trauma = 0.7 / 10.0;
-    let dmg = trigger.event().0;
-    commands.trigger(OnTrauma(base_trauma * dmg));
-
     if !health.is_dead() {
         let handle = player_assets
             .hurt_sounds
@@ -3,7 +3,7 @@
 //! Note that this is separate from the `movement` module as that could be used
 //! for other characters as well.
 
-use animation::{PlayerAnimationState, setup_player_animations};
+use animation::PlayerAnimationState;
 use avian3d::prelude::*;
 use bevy::prelude::*;
 use bevy_enhanced_input::prelude::*;
@@ -17,7 +17,7 @@ use navmesh_position::LastValidPlayerNavmeshPosition;
 
 use crate::{gameplay::player::movement::MovementStats, third_party::avian3d::CollisionLayer};
 
-use super::health::Health;
+use super::health::{Armor, Health, Shield};
 
 mod animation;
 pub(crate) mod assets;
@@ -25,11 +25,14 @@ pub(crate) mod camera;
 pub(crate) mod camera_shake;
 pub(crate) mod default_input;
 pub(crate) mod fall_damage;
+pub(crate) mod grenades;
 pub(crate) mod gunplay;
 pub(crate) mod lifecycle;
 pub(crate) mod movement;
 pub(crate) mod movement_sound;
 pub(crate) mod navmesh_position;
+pub(crate) mod pickup;
+pub(crate) mod weapons;
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<Player>();
@@ -47,6 +50,9 @@ pub(super) fn plugin(app: &mut App) {
         gunplay::plugin,
         camera_shake::plugin,
         lifecycle::plugin,
+        weapons::plugin,
+        pickup::plugin,
+        grenades::plugin,
     ));
     app.add_observer(setup_player);
     app.add_systems(PreUpdate, assert_only_one_player);
@@ -79,46 +85,51 @@ const PLAYER_HALF_HEIGHT: f32 = PLAYER_HEIGHT / 2.0;
 /// In this case, we use 30 cm of padding to make the player float nicely up stairs.
 const PLAYER_FLOAT_HEIGHT: f32 = PLAYER_HALF_HEIGHT + 0.5;
 
+/// The shield the player starts with, absorbing damage before their health.
+const PLAYER_SHIELD: f32 = 25.0;
+/// The fraction of damage the player's armor absorbs, once they have some.
+const PLAYER_ARMOR_ABSORPTION: f32 = 0.5;
+
 #[cfg_attr(feature = "hot_patch", hot)]
 fn setup_player(trigger: Trigger<OnAdd, Player>, mut commands: Commands) {
-    commands
-        .entity(trigger.target())
-        .insert((
-            RigidBody::Dynamic,
-            Actions::<DefaultInputContext>::default(),
-            // The player character needs to be configured as a dynamic rigid body of the physics
-            // engine.
-            Collider::capsule(PLAYER_RADIUS, PLAYER_CAPSULE_LENGTH),
-            MovementStats::default(),
-            // This is Tnua's interface component.
-            TnuaController::default(),
-            // A sensor shape is not strictly necessary, but without it we'll get weird results.
-            TnuaAvian3dSensorShape(Collider::cylinder(PLAYER_RADIUS - 0.01, 0.0)),
-            // Tnua can fix the rotation, but the character will still get rotated before it can do so.
-            // By locking the rotation we can prevent this.
-            LockedAxes::ROTATION_LOCKED,
-            // Movement feels nicer without friction.
-            Friction {
-                dynamic_coefficient: 0.0,
-                static_coefficient: 0.0,
-                combine_rule: CoefficientCombine::Multiply,
-            },
-            // For detecting the ground without Tnua's `is_airborne` state.
-            GroundCast::default(),
-            ColliderDensity(100.0),
-            CollisionLayers::new(
-                [CollisionLayer::Character, CollisionLayer::Player],
-                LayerMask::ALL,
-            ),
-            Health::new(100.0),
-            TnuaAnimatingState::<PlayerAnimationState>::default(),
-            children![(
-                Name::new("Player Landmass Character"),
-                Transform::from_xyz(0.0, -PLAYER_FLOAT_HEIGHT, 0.0),
-                LastValidPlayerNavmeshPosition::default(),
-            )],
-        ))
-        .observe(setup_player_animations);
+    commands.entity(trigger.target()).insert((
+        RigidBody::Dynamic,
+        Actions::<DefaultInputContext>::default(),
+        // The player character needs to be configured as a dynamic rigid body of the physics
+        // engine.
+        Collider::capsule(PLAYER_RADIUS, PLAYER_CAPSULE_LENGTH),
+        MovementStats::default(),
+        // This is Tnua's interface component.
+        TnuaController::default(),
+        // A sensor shape is not strictly necessary, but without it we'll get weird results.
+        TnuaAvian3dSensorShape(Collider::cylinder(PLAYER_RADIUS - 0.01, 0.0)),
+        // Tnua can fix the rotation, but the character will still get rotated before it can do so.
+        // By locking the rotation we can prevent this.
+        LockedAxes::ROTATION_LOCKED,
+        // Movement feels nicer without friction.
+        Friction {
+            dynamic_coefficient: 0.0,
+            static_coefficient: 0.0,
+            combine_rule: CoefficientCombine::Multiply,
+        },
+        // For detecting the ground without Tnua's `is_airborne` state.
+        GroundCast::default(),
+        ColliderDensity(100.0),
+        CollisionLayers::new(
+            [CollisionLayer::Character, CollisionLayer::Player],
+            LayerMask::ALL,
+        ),
+        Health::new(100.0),
+        Shield::new(PLAYER_SHIELD),
+        // The player has no armor until they buy some with upgrades.
+        Armor::new(0.0, PLAYER_ARMOR_ABSORPTION),
+        TnuaAnimatingState::<PlayerAnimationState>::default(),
+        children![(
+            Name::new("Player Landmass Character"),
+            Transform::from_xyz(0.0, -PLAYER_FLOAT_HEIGHT, 0.0),
+            LastValidPlayerNavmeshPosition::default(),
+        )],
+    ));
 }
 
 #[cfg_attr(feature = "hot_patch", hot)]
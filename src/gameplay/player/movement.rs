@@ -22,13 +22,17 @@ pub(super) fn plugin(app: &mut App) {
         Update,
         clear_accumulated_input.run_if(did_fixed_update_happen),
     );
+    app.add_systems(Update, recover_from_stagger);
     app.add_observer(jump);
     app.add_observer(accumulate_movement);
     app.add_observer(init_accumulated_input);
 
-    app.register_type::<AccumulatedInput>();
+    app.register_type::<(AccumulatedInput, Staggered)>();
 }
 
+/// How fast the player can move while [`Staggered`], relative to their normal speed.
+const STAGGER_SPEED_FACTOR: f32 = 0.3;
+
 #[derive(Component, Reflect, Default)]
 #[reflect(Component)]
 struct AccumulatedInput {
@@ -47,6 +51,11 @@ impl Default for MovementStats {
     }
 }
 
+/// The player was hit hard and moves slowly until the timer finishes.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub(crate) struct Staggered(pub(crate) Timer);
+
 #[cfg_attr(feature = "hot_patch", hot)]
 fn init_accumulated_input(trigger: Trigger<OnAdd, Player>, mut commands: Commands) {
     commands
@@ -71,11 +80,20 @@ fn clear_accumulated_input(mut accumulated_inputs: Query<&mut AccumulatedInput>)
 
 #[cfg_attr(feature = "hot_patch", hot)]
 fn apply_movement(
-    player_controller: Single<(&mut TnuaController, &AccumulatedInput, &MovementStats)>,
+    player_controller: Single<(
+        &mut TnuaController,
+        &AccumulatedInput,
+        &MovementStats,
+        Has<Staggered>,
+    )>,
     transform: Single<&Transform, With<PlayerCamera>>,
 ) {
-    let (mut controller, accumulated_input, movement_stats) = player_controller.into_inner();
-    let last_move = accumulated_input.last_move.unwrap_or_default();
+    let (mut controller, accumulated_input, movement_stats, staggered) =
+        player_controller.into_inner();
+    let mut last_move = accumulated_input.last_move.unwrap_or_default();
+    if staggered {
+        last_move *= STAGGER_SPEED_FACTOR;
+    }
     // Feed the basis every frame. Even if the player doesn't move - just use `desired_velocity:
     // Vec3::ZERO`. `TnuaController` starts without a basis, which will make the character collider
     // just fall.
@@ -94,6 +112,18 @@ fn apply_movement(
     });
 }
 
+fn recover_from_stagger(
+    mut staggered: Query<(Entity, &mut Staggered)>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut staggered) in &mut staggered {
+        if staggered.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Staggered>();
+        }
+    }
+}
+
 #[cfg_attr(feature = "hot_patch", hot)]
 fn jump(trigger: Trigger<Fired<Jump>>, mut controllers: Query<&mut TnuaController>) {
     let mut controller = controllers.get_mut(trigger.target()).unwrap();
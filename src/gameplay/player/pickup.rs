@@ -0,0 +1,244 @@
+//! Picking up, carrying and throwing props.
+//!
+//! A held prop is attached with a soft [`DistanceJoint`] to a kinematic anchor that floats in front of the
+//! [`PlayerCamera`], so it sways and bumps into things instead of clipping through them.
+//! Thrown explosive barrels explode on contact, so they can be lobbed into hordes.
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+#[cfg(feature = "hot_patch")]
+use bevy_simple_subsecond_system::hot;
+
+use crate::{
+    gameplay::{
+        explosion::ExplodeOnContact,
+        player::{
+            Player,
+            camera::PlayerCamera,
+            default_input::{DropProp, PickupProp},
+        },
+    },
+    props::generic::BarrelLargeClosed,
+    screens::Screen,
+    third_party::avian3d::CollisionLayer,
+};
+
+/// How far away props can be picked up from.
+const PICKUP_DISTANCE: f32 = 3.0;
+/// Props heavier than this are too heavy to pick up.
+const MAX_PICKUP_MASS: f32 = 400.0;
+/// How far in front of the camera a held prop floats.
+const HOLD_DISTANCE: f32 = 1.8;
+/// A held prop is dropped when it is pulled this far away from where it should be, e.g. when stuck behind a wall.
+const BREAK_DISTANCE: f32 = 2.5;
+/// The softness of the spring holding the prop. Higher values make it sway more.
+const HOLD_COMPLIANCE: f32 = 0.000_5;
+/// The impulse a prop is thrown with. Light props are thrown faster than heavy ones.
+const THROW_IMPULSE: f32 = 4_000.0;
+/// The fastest a prop can be thrown.
+const MAX_THROW_SPEED: f32 = 20.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(HeldProp, HoldAnchor)>();
+    app.add_observer(pick_up_or_drop_prop);
+    app.add_observer(throw_prop);
+    app.add_systems(
+        Update,
+        (move_hold_anchor, drop_prop_when_stuck)
+            .chain()
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// The prop the player is currently holding.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub(crate) struct HeldProp {
+    prop: Entity,
+    anchor: Entity,
+    joint: Entity,
+}
+
+impl HeldProp {
+    /// The prop being held.
+    pub(crate) fn prop(&self) -> Entity {
+        self.prop
+    }
+}
+
+/// The kinematic body a held prop is attached to.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+struct HoldAnchor;
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn pick_up_or_drop_prop(
+    trigger: Trigger<Started<PickupProp>>,
+    players: Query<Option<&HeldProp>, With<Player>>,
+    camera: Single<&Transform, With<PlayerCamera>>,
+    spatial_query: SpatialQuery,
+    collider_of: Query<&ColliderOf>,
+    bodies: Query<(&RigidBody, &ComputedMass)>,
+    mut colliders: Query<&mut CollisionLayers>,
+    children: Query<&Children>,
+    mut commands: Commands,
+) {
+    let player = trigger.target();
+    let Ok(held_prop) = players.get(player) else {
+        return;
+    };
+    if let Some(held_prop) = held_prop {
+        release_prop(&mut commands, player, held_prop, &children, &mut colliders);
+        return;
+    }
+
+    let filter = SpatialQueryFilter::default()
+        .with_mask([CollisionLayer::Default, CollisionLayer::Prop])
+        .with_excluded_entities([player]);
+    let Some(hit) = spatial_query.cast_ray(
+        camera.translation,
+        camera.forward(),
+        PICKUP_DISTANCE,
+        true,
+        &filter,
+    ) else {
+        return;
+    };
+    let Ok(&ColliderOf { body: prop }) = collider_of.get(hit.entity) else {
+        return;
+    };
+    let Ok((rigid_body, mass)) = bodies.get(prop) else {
+        return;
+    };
+    if !rigid_body.is_dynamic() || mass.value() > MAX_PICKUP_MASS {
+        return;
+    }
+
+    let anchor = commands
+        .spawn((
+            Name::new("Hold Anchor"),
+            HoldAnchor,
+            RigidBody::Kinematic,
+            Transform::from_translation(camera.translation + camera.forward() * HOLD_DISTANCE),
+            StateScoped(Screen::Gameplay),
+        ))
+        .id();
+    let joint = commands
+        .spawn((
+            Name::new("Hold Joint"),
+            DistanceJoint::new(anchor, prop)
+                .with_rest_length(0.0)
+                .with_compliance(HOLD_COMPLIANCE),
+            StateScoped(Screen::Gameplay),
+        ))
+        .id();
+    // Keep the prop from spinning wildly and from pushing the player around.
+    commands.entity(prop).insert(AngularDamping(5.0));
+    set_player_collisions(prop, false, &children, &mut colliders);
+    commands.entity(player).insert(HeldProp {
+        prop,
+        anchor,
+        joint,
+    });
+}
+
+#[cfg_attr(feature = "hot_patch", hot)]
+fn throw_prop(
+    trigger: Trigger<Started<DropProp>>,
+    players: Query<&HeldProp, With<Player>>,
+    camera: Single<&Transform, With<PlayerCamera>>,
+    masses: Query<&ComputedMass>,
+    barrels: Query<(), With<BarrelLargeClosed>>,
+    mut colliders: Query<&mut CollisionLayers>,
+    children: Query<&Children>,
+    mut commands: Commands,
+) {
+    let player = trigger.target();
+    let Ok(held_prop) = players.get(player) else {
+        return;
+    };
+    let prop = held_prop.prop;
+    release_prop(&mut commands, player, held_prop, &children, &mut colliders);
+
+    let mass = masses.get(prop).map_or(1.0, |mass| mass.value().max(1.0));
+    let speed = (THROW_IMPULSE / mass).min(MAX_THROW_SPEED);
+    commands
+        .entity(prop)
+        .insert(ExternalImpulse::new(camera.forward() * speed * mass));
+    if barrels.contains(prop) {
+        // Explode on whatever the barrel hits, except the player who threw it.
+        commands.entity(prop).insert(ExplodeOnContact {
+            layers: LayerMask::from([
+                CollisionLayer::Default,
+                CollisionLayer::Prop,
+                CollisionLayer::Npc,
+            ]),
+        });
+    }
+}
+
+/// Detaches the held prop, leaving it to physics.
+fn release_prop(
+    commands: &mut Commands,
+    player: Entity,
+    held_prop: &HeldProp,
+    children: &Query<&Children>,
+    colliders: &mut Query<&mut CollisionLayers>,
+) {
+    commands.entity(player).remove::<HeldProp>();
+    commands.entity(held_prop.joint).try_despawn();
+    commands.entity(held_prop.anchor).try_despawn();
+    if let Ok(mut prop) = commands.get_entity(held_prop.prop) {
+        prop.remove::<AngularDamping>();
+        set_player_collisions(held_prop.prop, true, children, colliders);
+    }
+}
+
+/// Enables or disables collisions between the colliders of a prop and the player.
+fn set_player_collisions(
+    prop: Entity,
+    enabled: bool,
+    children: &Query<&Children>,
+    colliders: &mut Query<&mut CollisionLayers>,
+) {
+    for collider in std::iter::once(prop).chain(children.iter_descendants(prop)) {
+        let Ok(mut layers) = colliders.get_mut(collider) else {
+            continue;
+        };
+        if enabled {
+            layers.filters.add(CollisionLayer::Player);
+        } else {
+            layers.filters.remove(CollisionLayer::Player);
+        }
+    }
+}
+
+fn move_hold_anchor(
+    camera: Single<&Transform, With<PlayerCamera>>,
+    mut anchors: Query<&mut Transform, (With<HoldAnchor>, Without<PlayerCamera>)>,
+) {
+    for mut anchor in &mut anchors {
+        anchor.translation = camera.translation + camera.forward() * HOLD_DISTANCE;
+    }
+}
+
+fn drop_prop_when_stuck(
+    player: Single<(Entity, &HeldProp), With<Player>>,
+    positions: Query<&Position>,
+    anchors: Query<&Transform, With<HoldAnchor>>,
+    mut colliders: Query<&mut CollisionLayers>,
+    children: Query<&Children>,
+    mut commands: Commands,
+) {
+    let (player, held_prop) = player.into_inner();
+    let distance = positions
+        .get(held_prop.prop)
+        .ok()
+        .zip(anchors.get(held_prop.anchor).ok())
+        .map(|(prop, anchor)| prop.distance(anchor.translation));
+    // The prop may also have been destroyed, e.g. by an explosion.
+    if distance.is_none_or(|distance| distance > BREAK_DISTANCE) {
+        release_prop(&mut commands, player, held_prop, &children, &mut colliders);
+    }
+}
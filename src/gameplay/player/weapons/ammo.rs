@@ -0,0 +1,120 @@
+//! Ammunition for the weapons.
+//!
+//! Every weapon has a magazine that is reloaded shell by shell from a reserve. Reloading starts when the player
+//! presses the reload button or tries to fire an empty weapon, and firing interrupts it as long as a shell is loaded.
+//! The reserves are refilled whenever a new wave starts preparing.
+
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+
+use crate::{
+    audio::sped_up_sound_effect,
+    gameplay::{
+        player::{
+            Player,
+            default_input::Reload,
+            gunplay::{FireCooldown, WeaponStats},
+        },
+        waves::WaveStartedPreparing,
+    },
+    screens::Screen,
+};
+
+use super::{WeaponInventory, WeaponSlot, assets::WeaponAssets};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Reloading>();
+    app.add_observer(reload_on_input);
+    app.add_observer(refill_reserves);
+    app.add_systems(Update, load_shells);
+}
+
+/// The shells of a weapon.
+#[derive(Debug, Clone, Reflect)]
+pub(crate) struct Ammo {
+    /// The shells in the magazine.
+    pub(crate) loaded: u32,
+    pub(crate) reserve: u32,
+}
+
+impl Ammo {
+    /// A full magazine and reserve.
+    pub(crate) fn full(stats: &WeaponStats) -> Self {
+        Self {
+            loaded: stats.magazine_size,
+            reserve: stats.reserve_size,
+        }
+    }
+
+    /// Whether there is a shell in the reserve and room for it in the magazine.
+    fn can_load(&self, stats: &WeaponStats) -> bool {
+        self.reserve > 0 && self.loaded < stats.magazine_size
+    }
+}
+
+/// The player is loading shells into the selected weapon, one each time the timer finishes.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub(crate) struct Reloading(Timer);
+
+/// Starts reloading the given weapon of the player, unless its magazine is full or its reserve is empty.
+pub(crate) fn start_reload(commands: &mut Commands, player: Entity, slot: &WeaponSlot) {
+    if !slot.ammo.can_load(&slot.stats) {
+        return;
+    }
+    commands
+        .entity(player)
+        .insert(Reloading(Timer::from_seconds(
+            1.0 / slot.stats.reload_rate,
+            TimerMode::Repeating,
+        )));
+}
+
+fn reload_on_input(
+    trigger: Trigger<Started<Reload>>,
+    player: Query<&WeaponInventory, (Without<Reloading>, Without<FireCooldown>)>,
+    mut commands: Commands,
+) {
+    let Ok(inventory) = player.get(trigger.target()) else {
+        return;
+    };
+    start_reload(&mut commands, trigger.target(), inventory.selected());
+}
+
+fn load_shells(
+    mut player: Query<(Entity, &mut WeaponInventory, &mut Reloading)>,
+    time: Res<Time>,
+    weapon_assets: Res<WeaponAssets>,
+    state: Res<State<Screen>>,
+    mut commands: Commands,
+) {
+    for (entity, mut inventory, mut reloading) in &mut player {
+        reloading.0.tick(time.delta());
+        let slot = inventory.selected_mut();
+        for _ in 0..reloading.0.times_finished_this_tick() {
+            if !slot.ammo.can_load(&slot.stats) {
+                break;
+            }
+            slot.ammo.reserve -= 1;
+            slot.ammo.loaded += 1;
+            if *state == Screen::Gameplay {
+                let sound = weapon_assets.get(slot.weapon).reload_sound.clone();
+                commands.spawn(sped_up_sound_effect(sound));
+            }
+        }
+        if !slot.ammo.can_load(&slot.stats) {
+            commands.entity(entity).remove::<Reloading>();
+        }
+    }
+}
+
+fn refill_reserves(
+    _trigger: Trigger<WaveStartedPreparing>,
+    mut inventory: Query<&mut WeaponInventory, With<Player>>,
+) {
+    for mut inventory in &mut inventory {
+        for slot in inventory.slots_mut() {
+            slot.ammo.reserve = slot.ammo.reserve.max(slot.stats.reserve_size);
+        }
+    }
+}
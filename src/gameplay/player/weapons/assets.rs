@@ -0,0 +1,75 @@
+//! Assets for the weapons.
+
+use bevy::{
+    asset::{UntypedAssetId, VisitAssetDependencies},
+    prelude::*,
+};
+use bevy_shuffle_bag::ShuffleBag;
+
+use crate::asset_tracking::LoadResource;
+
+use super::Weapon;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<WeaponAssets>();
+    app.load_resource::<WeaponAssets>();
+}
+
+/// The assets of every [`Weapon`], in the order of [`Weapon::ALL`].
+#[derive(Resource, Clone, Reflect)]
+#[reflect(Resource)]
+pub(crate) struct WeaponAssets([WeaponAssetSet; Weapon::ALL.len()]);
+
+#[derive(Clone, Reflect)]
+pub(crate) struct WeaponAssetSet {
+    pub(crate) view_model: Handle<Scene>,
+    pub(crate) shooting_sounds: ShuffleBag<Handle<AudioSource>>,
+    pub(crate) reload_sound: Handle<AudioSource>,
+}
+
+impl WeaponAssets {
+    pub(crate) fn get(&self, weapon: Weapon) -> &WeaponAssetSet {
+        &self.0[weapon as usize]
+    }
+
+    pub(crate) fn get_mut(&mut self, weapon: Weapon) -> &mut WeaponAssetSet {
+        &mut self.0[weapon as usize]
+    }
+}
+
+// The derive only supports fields that are handles or collections of handles,
+// so we have to visit the dependencies of each weapon ourselves.
+impl Asset for WeaponAssets {}
+
+impl VisitAssetDependencies for WeaponAssets {
+    fn visit_dependencies(&self, visit: &mut impl FnMut(UntypedAssetId)) {
+        for set in &self.0 {
+            set.view_model.visit_dependencies(visit);
+            set.shooting_sounds.visit_dependencies(visit);
+            set.reload_sound.visit_dependencies(visit);
+        }
+    }
+}
+
+impl FromWorld for WeaponAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        let rng = &mut rand::thread_rng();
+        Self(Weapon::ALL.map(|weapon| {
+            let definition = weapon.definition();
+            WeaponAssetSet {
+                view_model: assets.load(format!("{}#Scene0", definition.view_model)),
+                shooting_sounds: ShuffleBag::try_new(
+                    definition
+                        .shooting_sounds
+                        .iter()
+                        .map(|path| assets.load(*path))
+                        .collect::<Vec<_>>(),
+                    rng,
+                )
+                .unwrap(),
+                reload_sound: assets.load(definition.reload_sound),
+            }
+        }))
+    }
+}
@@ -0,0 +1,328 @@
+//! The player's weapons and switching between them.
+//!
+//! Every [`Weapon`] has a [`WeaponDefinition`] with its view model, sounds and base [`WeaponStats`].
+//! The player carries a [`WeaponInventory`] with the stats of each weapon they own, which upgrades modify,
+//! and which weapon is selected. Weapons are selected with the number keys, or cycled through with the scroll wheel
+//! and the gamepad.
+//!
+//! Only the pump shotgun has its own model and sounds so far. The other weapons reuse them as placeholders,
+//! told apart by a different shape and tint of the view model and a different pitch of the shooting sounds.
+
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+
+use crate::{
+    audio::sound_effect,
+    gameplay::player::{
+        Player,
+        default_input::{CycleWeapon, SelectWeapon},
+        gunplay::{FireCooldown, WeaponStats},
+    },
+};
+
+pub(crate) mod ammo;
+pub(crate) mod assets;
+
+use ammo::{Ammo, Reloading};
+use assets::WeaponAssets;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins((ammo::plugin, assets::plugin));
+
+    app.register_type::<WeaponInventory>();
+    app.register_type::<WeaponViewModel>();
+    app.add_observer(setup_weapon_inventory);
+    app.add_observer(select_weapon);
+    app.add_observer(cycle_weapon);
+    app.add_systems(Update, show_selected_view_model);
+}
+
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Weapon {
+    PumpShotgun,
+    SlugGun,
+    Scattergun,
+}
+
+impl Weapon {
+    /// Every weapon, in declaration order. Add new weapons here!
+    pub(crate) const ALL: [Weapon; 3] = [Weapon::PumpShotgun, Weapon::SlugGun, Weapon::Scattergun];
+
+    pub(crate) fn definition(self) -> WeaponDefinition {
+        match self {
+            Weapon::PumpShotgun => WeaponDefinition {
+                name: "Pump Shotgun",
+                view_model: "models/guns/pump_action_shotgun.gltf",
+                shooting_sounds: &[
+                    "audio/sound_effects/shoot/Shotgun_Shot-001.ogg",
+                    "audio/sound_effects/shoot/Shotgun_Shot-002.ogg",
+                    "audio/sound_effects/shoot/Shotgun_Shot-003.ogg",
+                    "audio/sound_effects/shoot/Shotgun_Shot-004.ogg",
+                ],
+                reload_sound: "audio/sound_effects/shoot/Shotgun_Pump.ogg",
+                view_model_transform: Transform::IDENTITY,
+                tint: Color::WHITE,
+                sound_speed: 1.0,
+                stats: WeaponStats {
+                    damage: 5.0,
+                    falloff_start: 8.0,
+                    falloff_end: 30.0,
+                    min_damage_fraction: 0.3,
+                    penetration: 1,
+                    penetration_damage_factor: 0.5,
+                    pellets: 16,
+                    spread_radius: 0.15,
+                    pushback: 12.0,
+                    extra_enemy_explosion_radius: 0.0,
+                    fire_rate: 1.8,
+                    reload_rate: 2.5,
+                    magazine_size: 6,
+                    reserve_size: 36,
+                },
+            },
+            Weapon::SlugGun => WeaponDefinition {
+                name: "Slug Gun",
+                view_model: "models/guns/pump_action_shotgun.gltf",
+                shooting_sounds: &[
+                    "audio/sound_effects/shoot/Shotgun_Shot-003.ogg",
+                    "audio/sound_effects/shoot/Shotgun_Shot-004.ogg",
+                ],
+                reload_sound: "audio/sound_effects/shoot/Shotgun_Pump.ogg",
+                // A long, slim barrel in blued steel, with a deeper boom.
+                view_model_transform: Transform::from_scale(Vec3::new(0.85, 0.85, 1.3)),
+                tint: Color::srgb(0.55, 0.6, 0.75),
+                sound_speed: 0.8,
+                stats: WeaponStats {
+                    damage: 60.0,
+                    falloff_start: 40.0,
+                    falloff_end: 120.0,
+                    min_damage_fraction: 0.6,
+                    penetration: 3,
+                    penetration_damage_factor: 0.75,
+                    pellets: 1,
+                    spread_radius: 0.005,
+                    pushback: 6.0,
+                    extra_enemy_explosion_radius: 0.0,
+                    fire_rate: 1.1,
+                    reload_rate: 2.0,
+                    magazine_size: 4,
+                    reserve_size: 20,
+                },
+            },
+            Weapon::Scattergun => WeaponDefinition {
+                name: "Scattergun",
+                view_model: "models/guns/pump_action_shotgun.gltf",
+                shooting_sounds: &[
+                    "audio/sound_effects/shoot/Shotgun_Shot-001.ogg",
+                    "audio/sound_effects/shoot/Shotgun_Shot-002.ogg",
+                ],
+                reload_sound: "audio/sound_effects/shoot/Shotgun_Pump.ogg",
+                // A short, stubby brass gun with a sharper bang.
+                view_model_transform: Transform::from_scale(Vec3::new(1.15, 1.15, 0.75)),
+                tint: Color::srgb(0.85, 0.65, 0.4),
+                sound_speed: 1.25,
+                stats: WeaponStats {
+                    damage: 3.0,
+                    falloff_start: 5.0,
+                    falloff_end: 20.0,
+                    min_damage_fraction: 0.2,
+                    penetration: 0,
+                    penetration_damage_factor: 0.5,
+                    pellets: 10,
+                    spread_radius: 0.25,
+                    pushback: 7.0,
+                    extra_enemy_explosion_radius: 0.0,
+                    fire_rate: 4.0,
+                    reload_rate: 3.0,
+                    magazine_size: 10,
+                    reserve_size: 60,
+                },
+            },
+        }
+    }
+}
+
+/// Everything that makes up a [`Weapon`].
+pub(crate) struct WeaponDefinition {
+    pub(crate) name: &'static str,
+    /// The glTF file of the view model.
+    /// Its animations are driven by the player's animation graph, so they must match the pump shotgun's.
+    pub(crate) view_model: &'static str,
+    pub(crate) shooting_sounds: &'static [&'static str],
+    /// Played when the weapon is cycled after a shot, when a shell is loaded and when the weapon is drawn.
+    pub(crate) reload_sound: &'static str,
+    /// Applied to the root of the view model, on top of the transforms animated in the glTF file.
+    pub(crate) view_model_transform: Transform,
+    /// Multiplied with the base color of every material of the view model.
+    pub(crate) tint: Color,
+    /// The playback speed of the shooting sounds. Faster sounds are higher pitched.
+    pub(crate) sound_speed: f32,
+    /// The stats the weapon starts with, before upgrades.
+    pub(crate) stats: WeaponStats,
+}
+
+/// The weapons the player owns.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub(crate) struct WeaponInventory {
+    slots: Vec<WeaponSlot>,
+    selected: usize,
+}
+
+/// A weapon in the [`WeaponInventory`], with its upgraded stats and its ammunition.
+#[derive(Debug, Clone, Reflect)]
+pub(crate) struct WeaponSlot {
+    pub(crate) weapon: Weapon,
+    pub(crate) stats: WeaponStats,
+    pub(crate) ammo: Ammo,
+}
+
+impl WeaponInventory {
+    /// An inventory with the given weapons, with the first one selected.
+    pub(crate) fn new(weapons: impl IntoIterator<Item = Weapon>) -> Self {
+        let slots = weapons
+            .into_iter()
+            .map(|weapon| {
+                let stats = weapon.definition().stats;
+                WeaponSlot {
+                    weapon,
+                    ammo: Ammo::full(&stats),
+                    stats,
+                }
+            })
+            .collect::<Vec<_>>();
+        assert!(!slots.is_empty(), "The player needs at least one weapon");
+        Self { slots, selected: 0 }
+    }
+
+    pub(crate) fn selected(&self) -> &WeaponSlot {
+        &self.slots[self.selected]
+    }
+
+    pub(crate) fn selected_mut(&mut self) -> &mut WeaponSlot {
+        &mut self.slots[self.selected]
+    }
+
+    /// The stats of the selected weapon.
+    pub(crate) fn stats(&self) -> &WeaponStats {
+        &self.selected().stats
+    }
+
+    pub(crate) fn slots_mut(&mut self) -> impl Iterator<Item = &mut WeaponSlot> {
+        self.slots.iter_mut()
+    }
+
+    /// Selects the weapon in the given slot. Returns whether the selection changed.
+    fn select(&mut self, index: usize) -> bool {
+        if index >= self.slots.len() || index == self.selected {
+            return false;
+        }
+        self.selected = index;
+        true
+    }
+
+    /// Selects the weapon `offset` slots after the selected one, wrapping around.
+    /// Returns whether the selection changed.
+    fn cycle(&mut self, offset: isize) -> bool {
+        let index = (self.selected as isize + offset).rem_euclid(self.slots.len() as isize);
+        self.select(index as usize)
+    }
+}
+
+/// The view model of a weapon. Only the selected weapon's view model is visible.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub(crate) struct WeaponViewModel(pub(crate) Weapon);
+
+fn setup_weapon_inventory(trigger: Trigger<OnAdd, Player>, mut commands: Commands) {
+    // Until there are weapon pickups, the player starts with every weapon.
+    commands
+        .entity(trigger.target())
+        .insert(WeaponInventory::new(Weapon::ALL));
+}
+
+fn select_weapon(
+    trigger: Trigger<Started<SelectWeapon>>,
+    mut inventory: Query<&mut WeaponInventory, Without<FireCooldown>>,
+    weapon_assets: Res<WeaponAssets>,
+    mut commands: Commands,
+) {
+    // The action's value is the number of the pressed key, starting at 1.
+    let Some(index) = (trigger.value.round() as usize).checked_sub(1) else {
+        return;
+    };
+    // Weapons cannot be switched while they are firing.
+    let Ok(mut inventory) = inventory.get_mut(trigger.target()) else {
+        return;
+    };
+    if inventory.select(index) {
+        switch_weapon(trigger.target(), &inventory, &weapon_assets, &mut commands);
+    }
+}
+
+fn cycle_weapon(
+    trigger: Trigger<Started<CycleWeapon>>,
+    mut inventory: Query<&mut WeaponInventory, Without<FireCooldown>>,
+    weapon_assets: Res<WeaponAssets>,
+    mut commands: Commands,
+) {
+    // Scroll wheels can report more than one line per frame, but we only ever switch by one weapon.
+    let offset = trigger.value.signum() as isize;
+    let Ok(mut inventory) = inventory.get_mut(trigger.target()) else {
+        return;
+    };
+    if inventory.cycle(offset) {
+        switch_weapon(trigger.target(), &inventory, &weapon_assets, &mut commands);
+    }
+}
+
+fn switch_weapon(
+    player: Entity,
+    inventory: &WeaponInventory,
+    weapon_assets: &WeaponAssets,
+    commands: &mut Commands,
+) {
+    // Reloading is specific to the weapon that was put away.
+    commands.entity(player).remove::<Reloading>();
+    let sound = weapon_assets
+        .get(inventory.selected().weapon)
+        .reload_sound
+        .clone();
+    commands.spawn(sound_effect(sound));
+}
+
+fn show_selected_view_model(
+    inventory: Single<&WeaponInventory, With<Player>>,
+    mut view_models: Query<(&WeaponViewModel, &mut Visibility)>,
+) {
+    let selected = inventory.selected().weapon;
+    for (view_model, mut visibility) in &mut view_models {
+        visibility.set_if_neq(if view_model.0 == selected {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycling_wraps_around() {
+        let mut inventory = WeaponInventory::new(Weapon::ALL);
+        assert!(!inventory.select(0));
+        assert!(!inventory.select(Weapon::ALL.len()));
+
+        assert!(inventory.cycle(-1));
+        assert_eq!(inventory.selected().weapon, Weapon::Scattergun);
+        assert!(inventory.cycle(1));
+        assert_eq!(inventory.selected().weapon, Weapon::PumpShotgun);
+        assert!(inventory.select(1));
+        assert_eq!(
+            inventory.stats().pellets,
+            Weapon::SlugGun.definition().stats.pellets
+        );
+    }
+}
@@ -0,0 +1,312 @@
+//! The compact binary format replays are stored in.
+//!
+//! All numbers are little endian. A file starts with a header:
+//! - the magic bytes `CBRP` and a version byte
+//! - the run's seed as `u64`
+//! - the game mode as `u8` (0 = normal, 1 = endless)
+//! - the number of frames as `u32`
+//!
+//! Each frame then consists of its virtual time step in microseconds as `u32`, the [`FrameFlags`] as `u16`
+//! and the values of the flagged actions: the movement as three `f32`s,
+//! the rotation after scaling it by the camera settings as two `f32`s,
+//! the picked upgrade as two `u8`s (the upgrade and its target, 0 for all weapons or the weapon's index plus 1),
+//! and the selected and cycled weapon as an `f32` each.
+
+use std::time::Duration;
+
+use anyhow::{Context as _, bail};
+use bevy::prelude::*;
+use bitflags::bitflags;
+
+use crate::gameplay::{
+    player::weapons::Weapon,
+    upgrades::{OfferedUpgrade, Upgrade, UpgradeTarget},
+    waves::GameMode,
+};
+
+const MAGIC: &[u8; 4] = b"CBRP";
+const VERSION: u8 = 7;
+
+/// A recorded run.
+#[derive(Debug, Clone)]
+pub(crate) struct Recording {
+    pub(crate) seed: u64,
+    pub(crate) game_mode: GameMode,
+    pub(crate) frames: Vec<RecordedFrame>,
+}
+
+/// The player's input during a single frame.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct RecordedFrame {
+    /// The frame's virtual time step, which is zero while the game is paused.
+    pub(crate) delta: Duration,
+    /// Whether the pause menu was open.
+    pub(crate) paused: bool,
+    pub(crate) movement: Option<Vec3>,
+    pub(crate) rotation: Option<Vec2>,
+    pub(crate) jump: bool,
+    pub(crate) shoot: bool,
+    pub(crate) reload: bool,
+    pub(crate) pickup_prop: bool,
+    pub(crate) drop_prop: bool,
+    pub(crate) throw_grenade: bool,
+    pub(crate) open_upgrade_menu: bool,
+    pub(crate) upgrade: Option<OfferedUpgrade>,
+    pub(crate) select_weapon: Option<f32>,
+    pub(crate) cycle_weapon: Option<f32>,
+}
+
+bitflags! {
+    struct FrameFlags: u16 {
+        const MOVEMENT = 1 << 0;
+        const ROTATION = 1 << 1;
+        const JUMP = 1 << 2;
+        const SHOOT = 1 << 3;
+        const OPEN_UPGRADE_MENU = 1 << 4;
+        const UPGRADE = 1 << 5;
+        const SELECT_WEAPON = 1 << 6;
+        const CYCLE_WEAPON = 1 << 7;
+        const RELOAD = 1 << 8;
+        const PICKUP_PROP = 1 << 9;
+        const DROP_PROP = 1 << 10;
+        const THROW_GRENADE = 1 << 11;
+        const PAUSED = 1 << 12;
+    }
+}
+
+impl Recording {
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(18 + self.frames.len() * 7);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.push(match self.game_mode {
+            GameMode::Endless => 1,
+            _ => 0,
+        });
+        bytes.extend_from_slice(&(self.frames.len() as u32).to_le_bytes());
+
+        for frame in &self.frames {
+            let micros = u32::try_from(frame.delta.as_micros()).unwrap_or(u32::MAX);
+            bytes.extend_from_slice(&micros.to_le_bytes());
+
+            let mut flags = FrameFlags::empty();
+            flags.set(FrameFlags::MOVEMENT, frame.movement.is_some());
+            flags.set(FrameFlags::ROTATION, frame.rotation.is_some());
+            flags.set(FrameFlags::JUMP, frame.jump);
+            flags.set(FrameFlags::SHOOT, frame.shoot);
+            flags.set(FrameFlags::OPEN_UPGRADE_MENU, frame.open_upgrade_menu);
+            flags.set(FrameFlags::UPGRADE, frame.upgrade.is_some());
+            flags.set(FrameFlags::SELECT_WEAPON, frame.select_weapon.is_some());
+            flags.set(FrameFlags::CYCLE_WEAPON, frame.cycle_weapon.is_some());
+            flags.set(FrameFlags::RELOAD, frame.reload);
+            flags.set(FrameFlags::PICKUP_PROP, frame.pickup_prop);
+            flags.set(FrameFlags::DROP_PROP, frame.drop_prop);
+            flags.set(FrameFlags::THROW_GRENADE, frame.throw_grenade);
+            flags.set(FrameFlags::PAUSED, frame.paused);
+            bytes.extend_from_slice(&flags.bits().to_le_bytes());
+
+            if let Some(movement) = frame.movement {
+                for value in movement.to_array() {
+                    bytes.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+            if let Some(rotation) = frame.rotation {
+                for value in rotation.to_array() {
+                    bytes.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+            if let Some(OfferedUpgrade { upgrade, target }) = frame.upgrade {
+                bytes.push(upgrade as u8);
+                bytes.push(match target {
+                    UpgradeTarget::AllWeapons => 0,
+                    UpgradeTarget::Weapon(weapon) => weapon as u8 + 1,
+                });
+            }
+            for value in [frame.select_weapon, frame.cycle_weapon]
+                .into_iter()
+                .flatten()
+            {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    pub(crate) fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let mut reader = ByteReader(bytes);
+        if reader.take::<4>()? != *MAGIC {
+            bail!("Not a replay file");
+        }
+        let version = reader.u8()?;
+        if version != VERSION {
+            bail!("Unsupported replay version {version}, expected {VERSION}");
+        }
+        let seed = u64::from_le_bytes(reader.take()?);
+        let game_mode = match reader.u8()? {
+            0 => GameMode::Normal,
+            1 => GameMode::Endless,
+            other => bail!("Unknown game mode {other}"),
+        };
+        let frame_count = u32::from_le_bytes(reader.take()?);
+
+        let frames = (0..frame_count)
+            .map(|index| {
+                reader
+                    .frame()
+                    .with_context(|| format!("Failed to read frame {index}"))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self {
+            seed,
+            game_mode,
+            frames,
+        })
+    }
+}
+
+struct ByteReader<'a>(&'a [u8]);
+
+impl ByteReader<'_> {
+    fn take<const N: usize>(&mut self) -> anyhow::Result<[u8; N]> {
+        let Some((bytes, rest)) = self.0.split_first_chunk::<N>() else {
+            bail!("Unexpected end of file");
+        };
+        self.0 = rest;
+        Ok(*bytes)
+    }
+
+    fn u8(&mut self) -> anyhow::Result<u8> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    fn f32(&mut self) -> anyhow::Result<f32> {
+        Ok(f32::from_le_bytes(self.take()?))
+    }
+
+    fn frame(&mut self) -> anyhow::Result<RecordedFrame> {
+        let delta = Duration::from_micros(u32::from_le_bytes(self.take()?).into());
+        let flags = FrameFlags::from_bits(u16::from_le_bytes(self.take()?))
+            .context("Unknown frame flags")?;
+        let movement = flags
+            .contains(FrameFlags::MOVEMENT)
+            .then(|| anyhow::Ok(Vec3::new(self.f32()?, self.f32()?, self.f32()?)))
+            .transpose()?;
+        let rotation = flags
+            .contains(FrameFlags::ROTATION)
+            .then(|| anyhow::Ok(Vec2::new(self.f32()?, self.f32()?)))
+            .transpose()?;
+        let upgrade = flags
+            .contains(FrameFlags::UPGRADE)
+            .then(|| {
+                let index = self.u8()?;
+                let upgrade = Upgrade::ALL
+                    .get(usize::from(index))
+                    .copied()
+                    .with_context(|| format!("Unknown upgrade {index}"))?;
+                let target = match self.u8()? {
+                    0 => UpgradeTarget::AllWeapons,
+                    index => Weapon::ALL
+                        .get(usize::from(index - 1))
+                        .copied()
+                        .map(UpgradeTarget::Weapon)
+                        .with_context(|| format!("Unknown weapon {}", index - 1))?,
+                };
+                anyhow::Ok(OfferedUpgrade { upgrade, target })
+            })
+            .transpose()?;
+        let select_weapon = flags
+            .contains(FrameFlags::SELECT_WEAPON)
+            .then(|| self.f32())
+            .transpose()?;
+        let cycle_weapon = flags
+            .contains(FrameFlags::CYCLE_WEAPON)
+            .then(|| self.f32())
+            .transpose()?;
+        Ok(RecordedFrame {
+            delta,
+            paused: flags.contains(FrameFlags::PAUSED),
+            movement,
+            rotation,
+            jump: flags.contains(FrameFlags::JUMP),
+            shoot: flags.contains(FrameFlags::SHOOT),
+            reload: flags.contains(FrameFlags::RELOAD),
+            pickup_prop: flags.contains(FrameFlags::PICKUP_PROP),
+            drop_prop: flags.contains(FrameFlags::DROP_PROP),
+            throw_grenade: flags.contains(FrameFlags::THROW_GRENADE),
+            open_upgrade_menu: flags.contains(FrameFlags::OPEN_UPGRADE_MENU),
+            upgrade,
+            select_weapon,
+            cycle_weapon,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recording() -> Recording {
+        let upgrade = Upgrade::ALL[Upgrade::ALL.len() - 1];
+        let weapon = Weapon::ALL[Weapon::ALL.len() - 1];
+        Recording {
+            seed: 0x0123_4567_89ab_cdef,
+            game_mode: GameMode::Endless,
+            frames: vec![
+                // Every flag set at once.
+                RecordedFrame {
+                    delta: Duration::from_micros(16_667),
+                    paused: true,
+                    movement: Some(Vec3::new(0.5, 0.0, -1.0)),
+                    rotation: Some(Vec2::new(-3.25, 1.5)),
+                    jump: true,
+                    shoot: true,
+                    reload: true,
+                    pickup_prop: true,
+                    drop_prop: true,
+                    throw_grenade: true,
+                    open_upgrade_menu: true,
+                    upgrade: Some(OfferedUpgrade {
+                        upgrade,
+                        target: UpgradeTarget::Weapon(weapon),
+                    }),
+                    select_weapon: Some(2.0),
+                    cycle_weapon: Some(-1.0),
+                },
+                // No flags set.
+                RecordedFrame {
+                    delta: Duration::from_micros(8_000),
+                    ..default()
+                },
+                RecordedFrame {
+                    delta: Duration::from_micros(15_625),
+                    upgrade: Some(OfferedUpgrade {
+                        upgrade: Upgrade::ALL[0],
+                        target: UpgradeTarget::AllWeapons,
+                    }),
+                    ..default()
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let recording = recording();
+        let read = Recording::from_bytes(&recording.to_bytes()).unwrap();
+        assert_eq!(read.seed, recording.seed);
+        assert_eq!(read.game_mode, recording.game_mode);
+        assert_eq!(read.frames, recording.frames);
+    }
+
+    #[test]
+    fn truncated_file_is_rejected() {
+        let bytes = recording().to_bytes();
+        for len in 0..bytes.len() {
+            assert!(
+                Recording::from_bytes(&bytes[..len]).is_err(),
+                "Accepted a file truncated to {len} bytes"
+            );
+        }
+    }
+}
@@ -0,0 +1,445 @@
+//! Recording and replaying runs.
+//!
+//! Every run records the values of the player's input actions and the upgrades picked,
+//! frame by frame, together with the run's seed. Replays feed these back as mocked actions
+//! instead of live input. The recorded virtual time steps are replayed as well, so the fixed timestep
+//! ticks exactly as often as it did during the original run. They are zero while the game is paused,
+//! so pauses do not advance the replay, and the frames the pause menu was open in are replayed
+//! with [`Pause`] set, so that the same systems run. Viewers cannot pause replays themselves,
+//! pressing Escape quits them instead.
+//!
+//! We record per rendered frame rather than per fixed tick, because input is read once per frame
+//! and much of the gameplay, like shooting and picking upgrades, reacts to it in [`Update`].
+//! Replaying the frame's time step reproduces the fixed ticks that happened within it.
+//!
+//! Both start on the frame after entering [`Screen::Gameplay`]. Entering it discards the part of
+//! the fixed timestep left over from the loading screen and sets up the replay's first frame.
+//!
+//! Rotations are recorded after scaling them by the camera settings, so that replays turn the
+//! camera the same way regardless of the viewer's settings.
+//!
+//! Replays can be started from the title screen, which plays the last recorded run,
+//! or from the command line with `--replay <path>`.
+
+use bevy::{input::common_conditions::input_just_pressed, prelude::*, time::TimeUpdateStrategy};
+use bevy_enhanced_input::prelude::*;
+
+use crate::{
+    Pause,
+    gameplay::{
+        player::{
+            Player,
+            camera::{CameraSensitivity, MouseInversion, scale_rotation},
+            default_input::{
+                CycleWeapon, DefaultInputContext, DropProp, Jump, Move, OpenUpgradeMenu,
+                PickupProp, Reload, Rotate, SelectWeapon, Shoot, ThrowGrenade,
+            },
+        },
+        rng::{GameplayRng, RequestedSeed},
+        upgrades::ApplyUpgrade,
+        waves::GameMode,
+    },
+    screens::Screen,
+};
+
+mod file;
+
+use file::RecordedFrame;
+pub(crate) use file::Recording;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<LastRecording>();
+    #[cfg(not(target_family = "wasm"))]
+    if let Some(recording) = recording_from_args() {
+        app.insert_resource(LastRecording(Some(recording)));
+        app.add_systems(
+            OnEnter(Screen::Title),
+            start_replay_from_args.run_if(run_once),
+        );
+    }
+
+    app.add_systems(
+        OnEnter(Screen::Gameplay),
+        (discard_fixed_overstep, start_recording, start_replay),
+    );
+    app.add_systems(OnExit(Screen::Gameplay), (finish_recording, finish_replay));
+
+    app.add_observer(record_move);
+    app.add_observer(record_rotate);
+    app.add_observer(record_jump);
+    app.add_observer(record_shoot);
+    app.add_observer(record_reload);
+    app.add_observer(record_pickup_prop);
+    app.add_observer(record_drop_prop);
+    app.add_observer(record_throw_grenade);
+    app.add_observer(record_open_upgrade_menu);
+    app.add_observer(record_upgrade);
+    app.add_observer(record_select_weapon);
+    app.add_observer(record_cycle_weapon);
+
+    app.add_systems(
+        Update,
+        quit_replay.run_if(
+            resource_exists::<Replay>
+                .and(in_state(Screen::Gameplay))
+                .and(input_just_pressed(KeyCode::Escape)),
+        ),
+    );
+    app.add_systems(
+        Update,
+        replay_upgrades.run_if(
+            resource_exists::<Replay>
+                .and(in_state(Screen::Gameplay))
+                .and(not(state_changed::<Screen>)),
+        ),
+    );
+    app.add_systems(
+        Last,
+        (
+            record_frame.run_if(resource_exists::<Recorder>),
+            advance_replay.run_if(resource_exists::<Replay>.and(in_state(Screen::Gameplay))),
+        )
+            // The frame that enters gameplay is not part of the run yet.
+            .run_if(not(state_changed::<Screen>)),
+    );
+}
+
+/// The most recently finished run, or the replay passed on the command line.
+#[derive(Resource, Default)]
+pub(crate) struct LastRecording(pub(crate) Option<Recording>);
+
+/// Records the current run.
+#[derive(Resource, Default)]
+struct Recorder {
+    frames: Vec<RecordedFrame>,
+    current: RecordedFrame,
+}
+
+/// Plays back a recording instead of live input.
+#[derive(Resource)]
+pub(crate) struct Replay {
+    recording: Recording,
+    /// The index of the frame currently being played.
+    frame: usize,
+    /// The seed that was requested before the replay started.
+    previous_seed: Option<u64>,
+}
+
+/// Observer for buttons that plays back the last recorded run.
+pub(crate) fn watch_last_replay(_trigger: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.queue(start_last_replay);
+}
+
+fn start_last_replay(world: &mut World) {
+    let Some(recording) = world.resource::<LastRecording>().0.clone() else {
+        warn!("There is no recorded run to replay");
+        return;
+    };
+    info!(
+        "Replaying {} frames with seed {}",
+        recording.frames.len(),
+        recording.seed
+    );
+    let previous_seed = world
+        .resource_mut::<RequestedSeed>()
+        .0
+        .replace(recording.seed);
+    world
+        .resource_mut::<NextState<GameMode>>()
+        .set(recording.game_mode.clone());
+    world
+        .resource_mut::<NextState<Screen>>()
+        .set(Screen::Loading);
+    world.insert_resource(Replay {
+        recording,
+        frame: 0,
+        previous_seed,
+    });
+}
+
+fn start_replay_from_args(mut commands: Commands) {
+    commands.queue(start_last_replay);
+}
+
+fn discard_fixed_overstep(mut time: ResMut<Time<Fixed>>) {
+    let overstep = time.overstep();
+    time.discard_overstep(overstep);
+}
+
+fn start_recording(replay: Option<Res<Replay>>, mut commands: Commands) {
+    if replay.is_none() {
+        commands.init_resource::<Recorder>();
+    }
+}
+
+/// Prepares the time step and actions of the replay's first frame.
+fn start_replay(
+    replay: Option<ResMut<Replay>>,
+    mut actions: Query<&mut Actions<DefaultInputContext>, With<Player>>,
+    pause: Res<State<Pause>>,
+    mut next_pause: ResMut<NextState<Pause>>,
+    mut commands: Commands,
+) {
+    let Some(mut replay) = replay else {
+        return;
+    };
+    replay.frame = 0;
+    if let Some(frame) = replay.recording.frames.first() {
+        play_frame(
+            frame,
+            actions.single_mut().ok().as_deref_mut(),
+            (&pause, &mut next_pause),
+            &mut commands,
+        );
+    }
+}
+
+fn finish_recording(
+    recorder: Option<ResMut<Recorder>>,
+    rng: Res<GameplayRng>,
+    game_mode: Res<State<GameMode>>,
+    mut last_recording: ResMut<LastRecording>,
+    mut commands: Commands,
+) {
+    let Some(mut recorder) = recorder else {
+        return;
+    };
+    commands.remove_resource::<Recorder>();
+    let recording = Recording {
+        seed: rng.seed(),
+        game_mode: game_mode.get().clone(),
+        frames: std::mem::take(&mut recorder.frames),
+    };
+    #[cfg(not(target_family = "wasm"))]
+    save_recording(&recording);
+    last_recording.0 = Some(recording);
+}
+
+fn finish_replay(
+    replay: Option<Res<Replay>>,
+    mut requested_seed: ResMut<RequestedSeed>,
+    mut commands: Commands,
+) {
+    let Some(replay) = replay else {
+        return;
+    };
+    requested_seed.0 = replay.previous_seed;
+    commands.remove_resource::<Replay>();
+    commands.insert_resource(TimeUpdateStrategy::Automatic);
+}
+
+fn record_move(trigger: Trigger<Fired<Move>>, recorder: Option<ResMut<Recorder>>) {
+    if let Some(mut recorder) = recorder {
+        recorder.current.movement = Some(trigger.value);
+    }
+}
+
+/// Records the rotation after scaling it by the camera settings, which replays skip.
+fn record_rotate(
+    trigger: Trigger<Fired<Rotate>>,
+    recorder: Option<ResMut<Recorder>>,
+    sensitivity: Res<CameraSensitivity>,
+    mouse_inversion: Res<MouseInversion>,
+) {
+    if let Some(mut recorder) = recorder {
+        recorder.current.rotation = Some(scale_rotation(
+            trigger.value,
+            &sensitivity,
+            &mouse_inversion,
+        ));
+    }
+}
+
+fn record_jump(_trigger: Trigger<Fired<Jump>>, recorder: Option<ResMut<Recorder>>) {
+    if let Some(mut recorder) = recorder {
+        recorder.current.jump = true;
+    }
+}
+
+fn record_shoot(_trigger: Trigger<Fired<Shoot>>, recorder: Option<ResMut<Recorder>>) {
+    if let Some(mut recorder) = recorder {
+        recorder.current.shoot = true;
+    }
+}
+
+fn record_reload(_trigger: Trigger<Fired<Reload>>, recorder: Option<ResMut<Recorder>>) {
+    if let Some(mut recorder) = recorder {
+        recorder.current.reload = true;
+    }
+}
+
+fn record_pickup_prop(_trigger: Trigger<Fired<PickupProp>>, recorder: Option<ResMut<Recorder>>) {
+    if let Some(mut recorder) = recorder {
+        recorder.current.pickup_prop = true;
+    }
+}
+
+fn record_drop_prop(_trigger: Trigger<Fired<DropProp>>, recorder: Option<ResMut<Recorder>>) {
+    if let Some(mut recorder) = recorder {
+        recorder.current.drop_prop = true;
+    }
+}
+
+fn record_throw_grenade(
+    _trigger: Trigger<Fired<ThrowGrenade>>,
+    recorder: Option<ResMut<Recorder>>,
+) {
+    if let Some(mut recorder) = recorder {
+        recorder.current.throw_grenade = true;
+    }
+}
+
+fn record_open_upgrade_menu(
+    _trigger: Trigger<Fired<OpenUpgradeMenu>>,
+    recorder: Option<ResMut<Recorder>>,
+) {
+    if let Some(mut recorder) = recorder {
+        recorder.current.open_upgrade_menu = true;
+    }
+}
+
+fn record_upgrade(trigger: Trigger<ApplyUpgrade>, recorder: Option<ResMut<Recorder>>) {
+    if let Some(mut recorder) = recorder {
+        recorder.current.upgrade = Some(trigger.0);
+    }
+}
+
+fn record_select_weapon(trigger: Trigger<Fired<SelectWeapon>>, recorder: Option<ResMut<Recorder>>) {
+    if let Some(mut recorder) = recorder {
+        recorder.current.select_weapon = Some(trigger.value);
+    }
+}
+
+fn record_cycle_weapon(trigger: Trigger<Fired<CycleWeapon>>, recorder: Option<ResMut<Recorder>>) {
+    if let Some(mut recorder) = recorder {
+        recorder.current.cycle_weapon = Some(trigger.value);
+    }
+}
+
+/// Records the frame's virtual time step, which is zero if the game was paused before the frame started.
+fn record_frame(
+    mut recorder: ResMut<Recorder>,
+    time: Res<Time<Virtual>>,
+    pause: Res<State<Pause>>,
+) {
+    let mut frame = std::mem::take(&mut recorder.current);
+    frame.delta = time.delta();
+    frame.paused = pause.get().0;
+    recorder.frames.push(frame);
+}
+
+/// Prepares the time step and actions of the next frame.
+/// Runs at the end of a frame, so that both are in place before input is processed.
+fn advance_replay(
+    mut replay: ResMut<Replay>,
+    mut actions: Query<&mut Actions<DefaultInputContext>, With<Player>>,
+    pause: Res<State<Pause>>,
+    mut next_pause: ResMut<NextState<Pause>>,
+    mut commands: Commands,
+) {
+    replay.frame += 1;
+    let Some(frame) = replay.recording.frames.get(replay.frame) else {
+        if replay.frame == replay.recording.frames.len() {
+            info!("Replay finished");
+            commands.insert_resource(TimeUpdateStrategy::Automatic);
+        }
+        return;
+    };
+    play_frame(
+        frame,
+        actions.single_mut().ok().as_deref_mut(),
+        (&pause, &mut next_pause),
+        &mut commands,
+    );
+}
+
+/// Sets up the next update to use the time step, pause state and actions of a recorded frame.
+fn play_frame(
+    frame: &RecordedFrame,
+    actions: Option<&mut Actions<DefaultInputContext>>,
+    (pause, next_pause): (&State<Pause>, &mut NextState<Pause>),
+    commands: &mut Commands,
+) {
+    commands.insert_resource(TimeUpdateStrategy::ManualDuration(frame.delta));
+    if pause.get().0 != frame.paused {
+        next_pause.set(Pause(frame.paused));
+    }
+
+    // Input is blocked while a menu is open, in which case the player has no actions.
+    let Some(actions) = actions else {
+        return;
+    };
+    mock_action::<Move>(actions, frame.movement);
+    mock_action::<Rotate>(actions, frame.rotation);
+    mock_action::<Jump>(actions, frame.jump.then_some(true));
+    mock_action::<Shoot>(actions, frame.shoot.then_some(true));
+    mock_action::<Reload>(actions, frame.reload.then_some(true));
+    mock_action::<PickupProp>(actions, frame.pickup_prop.then_some(true));
+    mock_action::<DropProp>(actions, frame.drop_prop.then_some(true));
+    mock_action::<ThrowGrenade>(actions, frame.throw_grenade.then_some(true));
+    mock_action::<OpenUpgradeMenu>(actions, frame.open_upgrade_menu.then_some(true));
+    mock_action::<SelectWeapon>(actions, frame.select_weapon);
+    mock_action::<CycleWeapon>(actions, frame.cycle_weapon);
+}
+
+/// Mocks an action for the next update, overriding live input.
+/// Actions that were not fired are mocked as well, so that live input cannot fire them.
+fn mock_action<A: InputAction>(actions: &mut Actions<DefaultInputContext>, value: Option<A::Output>)
+where
+    A::Output: Default + Into<ActionValue>,
+{
+    match value {
+        Some(value) => actions.mock_once::<A>(ActionState::Fired, value),
+        None => actions.mock_once::<A>(ActionState::None, A::Output::default()),
+    }
+}
+
+fn quit_replay(mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
+
+fn replay_upgrades(replay: Res<Replay>, mut commands: Commands) {
+    let upgrade = replay
+        .recording
+        .frames
+        .get(replay.frame)
+        .and_then(|frame| frame.upgrade);
+    if let Some(upgrade) = upgrade {
+        commands.trigger(ApplyUpgrade(upgrade));
+    }
+}
+
+/// The folder in the [persistent storage](crate::persistence) that replays are saved to.
+#[cfg(not(target_family = "wasm"))]
+const REPLAY_DIRECTORY: &str = "replays";
+
+/// Writes a recording to the replay directory, named after the time it was saved.
+#[cfg(not(target_family = "wasm"))]
+fn save_recording(recording: &Recording) {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let key = format!("{REPLAY_DIRECTORY}/{timestamp}.cbreplay");
+    match crate::persistence::write_bytes(&key, &recording.to_bytes()) {
+        Ok(path) => info!("Saved replay to {}", path.display()),
+        Err(err) => error!("Failed to save replay: {err:#}"),
+    }
+}
+
+/// Reads the replay passed as `--replay <path>`, if any.
+#[cfg(not(target_family = "wasm"))]
+fn recording_from_args() -> Option<Recording> {
+    let mut args = std::env::args().skip_while(|arg| arg != "--replay").skip(1);
+    let path = args.next()?;
+    let recording = std::fs::read(&path)
+        .map_err(anyhow::Error::from)
+        .and_then(|bytes| Recording::from_bytes(&bytes));
+    match recording {
+        Ok(recording) => Some(recording),
+        Err(err) => {
+            error!("Failed to load replay \"{path}\": {err:#}");
+            None
+        }
+    }
+}
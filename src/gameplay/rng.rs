@@ -0,0 +1,104 @@
+//! Seeded randomness for the gameplay simulation, so that a run can be reproduced from its seed.
+//!
+//! Every subsystem draws from its own [`RngStream`], so that e.g. shooting more pellets
+//! does not change which enemies the next wave spawns.
+//! Purely cosmetic randomness such as sound variations keeps using `rand::thread_rng()`.
+
+use bevy::prelude::*;
+use rand::SeedableRng as _;
+use rand_chacha::ChaCha8Rng;
+
+use crate::screens::Screen;
+
+pub(crate) fn plugin(app: &mut App) {
+    app.register_type::<RequestedSeed>();
+    app.insert_resource(RequestedSeed(seed_from_args()));
+    app.init_resource::<GameplayRng>();
+
+    app.add_systems(OnEnter(Screen::Gameplay), reseed_gameplay_rng);
+}
+
+/// The seed the next run will use. `None` picks a random seed.
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub(crate) struct RequestedSeed(pub(crate) Option<u64>);
+
+/// The independent random number streams of the gameplay simulation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RngStream {
+    /// Packet, spawner and spawn position selection.
+    Waves,
+    /// Pellet spread.
+    Gunplay,
+    /// Attack speeds, staggers and grunts.
+    Npc,
+    /// Which gibs are spawned.
+    Gore,
+    /// Which upgrades are offered.
+    Upgrades,
+}
+
+impl RngStream {
+    const ALL: [Self; 5] = [
+        Self::Waves,
+        Self::Gunplay,
+        Self::Npc,
+        Self::Gore,
+        Self::Upgrades,
+    ];
+}
+
+#[derive(Resource, Debug)]
+pub(crate) struct GameplayRng {
+    seed: u64,
+    streams: [ChaCha8Rng; RngStream::ALL.len()],
+}
+
+impl GameplayRng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            streams: RngStream::ALL.map(|stream| {
+                let mut rng = ChaCha8Rng::seed_from_u64(seed);
+                rng.set_stream(stream as u64);
+                rng
+            }),
+        }
+    }
+
+    pub(crate) fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub(crate) fn stream(&mut self, stream: RngStream) -> &mut ChaCha8Rng {
+        &mut self.streams[stream as usize]
+    }
+}
+
+impl Default for GameplayRng {
+    fn default() -> Self {
+        Self::new(rand::random())
+    }
+}
+
+fn reseed_gameplay_rng(mut commands: Commands, requested_seed: Res<RequestedSeed>) {
+    let rng = match requested_seed.0 {
+        Some(seed) => GameplayRng::new(seed),
+        None => GameplayRng::default(),
+    };
+    info!("Gameplay seed: {}", rng.seed());
+    commands.insert_resource(rng);
+}
+
+/// Reads the seed passed as `--seed <number>`, if any.
+fn seed_from_args() -> Option<u64> {
+    let mut args = std::env::args().skip_while(|arg| arg != "--seed").skip(1);
+    let seed = args.next()?;
+    match seed.parse() {
+        Ok(seed) => Some(seed),
+        Err(err) => {
+            error!("Invalid seed \"{seed}\", using a random one instead: {err}");
+            None
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! The best runs of each [`GameMode`], stored across restarts.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{gameplay::waves::GameMode, persistence};
+
+/// The key the leaderboard is stored under.
+const STORAGE_KEY: &str = "leaderboard.ron";
+
+/// How many runs are kept per game mode.
+pub(crate) const LEADERBOARD_SIZE: usize = 10;
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(Leaderboard::load());
+}
+
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+pub(crate) struct Leaderboard {
+    #[serde(default)]
+    normal: Vec<LeaderboardEntry>,
+    #[serde(default)]
+    endless: Vec<LeaderboardEntry>,
+}
+
+/// A finished run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct LeaderboardEntry {
+    pub(crate) score: u32,
+    /// The number of waves reached.
+    pub(crate) waves: usize,
+    pub(crate) time_secs: f32,
+    /// The number of explosions in the longest chain reaction.
+    pub(crate) longest_chain: u32,
+    pub(crate) seed: u64,
+    /// When the run finished, in seconds since the Unix epoch.
+    pub(crate) finished_at: u64,
+}
+
+impl Leaderboard {
+    fn load() -> Self {
+        let text = match persistence::read(STORAGE_KEY) {
+            Ok(Some(text)) => text,
+            Ok(None) => return Self::default(),
+            Err(err) => {
+                error!("Failed to load the leaderboard: {err:#}");
+                return Self::default();
+            }
+        };
+        ron::from_str(&text).unwrap_or_else(|err| {
+            error!("Failed to parse the leaderboard, starting a new one: {err}");
+            Self::default()
+        })
+    }
+
+    fn save(&self) {
+        let result = ron::ser::to_string_pretty(self, default())
+            .map_err(anyhow::Error::from)
+            .and_then(|text| persistence::write(STORAGE_KEY, &text));
+        if let Err(err) = result {
+            error!("Failed to save the leaderboard: {err:#}");
+        }
+    }
+
+    /// The best runs of the given game mode, best first.
+    pub(crate) fn entries(&self, game_mode: &GameMode) -> &[LeaderboardEntry] {
+        match game_mode {
+            GameMode::Normal => &self.normal,
+            GameMode::Endless => &self.endless,
+            GameMode::Indeterminate => &[],
+        }
+    }
+
+    /// Adds a run to the leaderboard and saves it.
+    /// Returns the run's rank if it made it onto the leaderboard.
+    pub(crate) fn submit(
+        &mut self,
+        game_mode: &GameMode,
+        entry: LeaderboardEntry,
+    ) -> Option<usize> {
+        let rank = self.insert(game_mode, entry);
+        if rank.is_some() {
+            self.save();
+        }
+        rank
+    }
+
+    fn insert(&mut self, game_mode: &GameMode, entry: LeaderboardEntry) -> Option<usize> {
+        let entries = match game_mode {
+            GameMode::Normal => &mut self.normal,
+            GameMode::Endless => &mut self.endless,
+            GameMode::Indeterminate => {
+                warn!("Not submitting a run without a game mode to the leaderboard");
+                return None;
+            }
+        };
+        // Ties go to the earlier run.
+        let rank = entries.partition_point(|other| other.score >= entry.score);
+        if rank >= LEADERBOARD_SIZE {
+            return None;
+        }
+        entries.insert(rank, entry);
+        entries.truncate(LEADERBOARD_SIZE);
+        Some(rank)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(score: u32) -> LeaderboardEntry {
+        LeaderboardEntry {
+            score,
+            waves: 1,
+            time_secs: 60.0,
+            longest_chain: 1,
+            seed: 0,
+            finished_at: 0,
+        }
+    }
+
+    #[test]
+    fn keeps_the_best_runs_per_game_mode_in_order() {
+        let mut leaderboard = Leaderboard::default();
+        for score in 1..=LEADERBOARD_SIZE as u32 {
+            leaderboard.insert(&GameMode::Normal, entry(score * 10));
+        }
+
+        assert_eq!(leaderboard.insert(&GameMode::Normal, entry(5)), None);
+        assert_eq!(leaderboard.insert(&GameMode::Normal, entry(45)), Some(6));
+        // Ties go to the earlier run.
+        assert_eq!(leaderboard.insert(&GameMode::Normal, entry(90)), Some(2));
+        assert_eq!(leaderboard.insert(&GameMode::Endless, entry(1)), Some(0));
+
+        let scores = leaderboard
+            .entries(&GameMode::Normal)
+            .iter()
+            .map(|entry| entry.score)
+            .collect::<Vec<_>>();
+        assert_eq!(scores, [100, 90, 90, 80, 70, 60, 50, 45, 40, 30]);
+        assert_eq!(leaderboard.entries(&GameMode::Endless).len(), 1);
+    }
+}
@@ -0,0 +1,276 @@
+//! The score of the current run.
+//!
+//! Points are awarded for kills depending on the [`SpawnVariant`], for chain reaction combos
+//! and for waves cleared without taking damage. Winning the game adds a bonus for finishing quickly.
+
+pub(crate) mod leaderboard;
+
+use std::time::Duration;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use leaderboard::{Leaderboard, LeaderboardEntry};
+
+use crate::{
+    gameplay::{
+        combo::{ChainFinished, Chains},
+        health::{DamageKind, OnDamage, OnDeath},
+        player::Player,
+        replay::Replay,
+        rng::GameplayRng,
+        time::GameplayTime,
+        waves::{GameMode, SpawnVariant, WaveAdvanced, Waves},
+    },
+    screens::Screen,
+};
+
+/// The bonus for clearing a wave without taking any damage.
+const FLAWLESS_WAVE_BONUS: u32 = 500;
+
+/// Winning faster than this awards a time bonus.
+const TIME_BONUS_PAR: Duration = Duration::from_secs(20 * 60);
+
+/// The time bonus for every second the game was won faster than [`TIME_BONUS_PAR`].
+const TIME_BONUS_PER_SECOND: f32 = 5.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins(leaderboard::plugin);
+
+    app.register_type::<Score>();
+    app.init_resource::<Score>();
+    app.add_systems(OnEnter(Screen::Gameplay), reset_score);
+
+    app.add_observer(score_kill);
+    app.add_observer(score_chain);
+    app.add_observer(track_player_damage);
+    app.add_observer(score_flawless_wave);
+}
+
+#[derive(Resource, Debug, Default, Clone, Reflect)]
+#[reflect(Resource)]
+pub(crate) struct Score {
+    /// Points for killed enemies and destroyed barrels.
+    pub(crate) kills: u32,
+    /// Bonus points for chain reactions.
+    pub(crate) combos: u32,
+    /// Bonus points for waves cleared without taking damage.
+    pub(crate) flawless_waves: u32,
+    /// Whether the player took damage during the current wave.
+    damaged_this_wave: bool,
+}
+
+impl Score {
+    /// The score without the time bonus, which is only known once the game is won.
+    pub(crate) fn total(&self) -> u32 {
+        self.kills + self.combos + self.flawless_waves
+    }
+
+    /// The bonus for winning the game after `elapsed` time.
+    pub(crate) fn time_bonus(elapsed: Duration) -> u32 {
+        let seconds_left = TIME_BONUS_PAR.saturating_sub(elapsed).as_secs_f32();
+        (seconds_left * TIME_BONUS_PER_SECOND).round() as u32
+    }
+}
+
+/// A [`SystemParam`] for finishing the current run and submitting it to the leaderboard.
+#[derive(SystemParam)]
+pub(crate) struct RunSubmission<'w, 's> {
+    score: Res<'w, Score>,
+    chains: Res<'w, Chains>,
+    gameplay_time: Res<'w, GameplayTime>,
+    rng: Res<'w, GameplayRng>,
+    game_mode: Res<'w, State<GameMode>>,
+    waves: Query<'w, 's, &'static Waves>,
+    replay: Option<Res<'w, Replay>>,
+    leaderboard: ResMut<'w, Leaderboard>,
+}
+
+/// The outcome of a finished run.
+pub(crate) struct FinishedRun {
+    pub(crate) score: u32,
+    pub(crate) time_bonus: u32,
+    /// The rank on the leaderboard, if the run made it onto it.
+    pub(crate) rank: Option<usize>,
+}
+
+impl RunSubmission<'_, '_> {
+    /// Computes the final score and submits the run to the leaderboard.
+    /// Replays are scored, but not submitted.
+    pub(crate) fn submit(&mut self, won: bool) -> FinishedRun {
+        let elapsed = self.gameplay_time.elapsed();
+        let time_bonus = if won { Score::time_bonus(elapsed) } else { 0 };
+        let score = self.score.total() + time_bonus;
+        let entry = LeaderboardEntry {
+            score,
+            waves: self
+                .waves
+                .single()
+                .map_or(0, |waves| self.waves_reached(waves)),
+            time_secs: elapsed.as_secs_f32(),
+            longest_chain: self.chains.longest().explosions,
+            seed: self.rng.seed(),
+            finished_at: unix_timestamp(),
+        };
+        let rank = if self.replay.is_some() {
+            None
+        } else {
+            self.leaderboard.submit(self.game_mode.get(), entry)
+        };
+        FinishedRun {
+            score,
+            time_bonus,
+            rank,
+        }
+    }
+
+    /// The number of the wave the run ended in.
+    fn waves_reached(&self, waves: &Waves) -> usize {
+        let reached = waves.current_wave_index() + 1;
+        match self.game_mode.get() {
+            // Winning advances past the last wave.
+            GameMode::Normal => reached.min(waves.total_waves()),
+            GameMode::Endless | GameMode::Indeterminate => reached,
+        }
+    }
+
+    pub(crate) fn entries(&self) -> &[LeaderboardEntry] {
+        self.leaderboard.entries(self.game_mode.get())
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn unix_timestamp() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(target_family = "wasm")]
+fn unix_timestamp() -> u64 {
+    // `SystemTime` is not available on the web.
+    (web_sys::js_sys::Date::now() / 1000.0) as u64
+}
+
+impl SpawnVariant {
+    /// The points awarded for killing or destroying what was spawned as this variant.
+    fn kill_score(self) -> u32 {
+        match self {
+            SpawnVariant::BasicEnemy => 100,
+            SpawnVariant::BigEnemy => 250,
+            SpawnVariant::SmallEnemy => 50,
+            SpawnVariant::Spitter => 150,
+            SpawnVariant::ExplosiveBarrel => 10,
+        }
+    }
+}
+
+fn reset_score(mut score: ResMut<Score>) {
+    *score = Score::default();
+}
+
+fn score_kill(trigger: Trigger<OnDeath>, variants: Query<&SpawnVariant>, mut score: ResMut<Score>) {
+    // NPCs that are removed for getting stuck were not killed by the player.
+    if trigger.kind == DamageKind::Despawn {
+        return;
+    }
+    if let Ok(variant) = variants.get(trigger.target()) {
+        score.kills += variant.kill_score();
+    }
+}
+
+fn score_chain(trigger: Trigger<ChainFinished>, mut score: ResMut<Score>) {
+    score.combos += trigger.stats.score();
+}
+
+fn track_player_damage(
+    trigger: Trigger<OnDamage>,
+    player: Query<(), With<Player>>,
+    mut score: ResMut<Score>,
+) {
+    if player.contains(trigger.target()) {
+        score.damaged_this_wave = true;
+    }
+}
+
+fn score_flawless_wave(_trigger: Trigger<WaveAdvanced>, mut score: ResMut<Score>) {
+    if !score.damaged_this_wave {
+        score.flawless_waves += FLAWLESS_WAVE_BONUS;
+    }
+    score.damaged_this_wave = false;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headless::{headless_app, run_updates};
+
+    fn score_app() -> App {
+        let mut app = headless_app();
+        app.init_resource::<Score>();
+        app.add_observer(score_kill);
+        app.add_observer(track_player_damage);
+        app.add_observer(score_flawless_wave);
+        run_updates(&mut app, 1);
+        app
+    }
+
+    fn kill(app: &mut App, entity: Entity, kind: DamageKind) {
+        app.world_mut()
+            .trigger_targets(OnDeath::from(OnDamage::new(100.0, kind)), entity);
+    }
+
+    #[test]
+    fn kills_score_by_spawn_variant() {
+        let mut app = score_app();
+        let enemy = app.world_mut().spawn(SpawnVariant::BigEnemy).id();
+        let barrel = app.world_mut().spawn(SpawnVariant::ExplosiveBarrel).id();
+        let stuck = app.world_mut().spawn(SpawnVariant::BasicEnemy).id();
+        let unknown = app.world_mut().spawn_empty().id();
+
+        kill(&mut app, enemy, DamageKind::Bullet);
+        kill(&mut app, barrel, DamageKind::Explosion);
+        // Despawning stuck enemies and killing things that were not spawned by a wave is worth nothing.
+        kill(&mut app, stuck, DamageKind::Despawn);
+        kill(&mut app, unknown, DamageKind::Bullet);
+
+        let score = app.world().resource::<Score>();
+        assert_eq!(score.kills, 250 + 10);
+        assert_eq!(score.total(), score.kills);
+    }
+
+    #[test]
+    fn only_waves_without_damage_are_flawless() {
+        let mut app = score_app();
+        let player = app.world_mut().spawn(Player).id();
+        let enemy = app.world_mut().spawn_empty().id();
+
+        // Other things getting hurt do not count.
+        app.world_mut()
+            .trigger_targets(OnDamage::new(10.0, DamageKind::Bullet), enemy);
+        app.world_mut().trigger(WaveAdvanced);
+        app.world_mut()
+            .trigger_targets(OnDamage::new(10.0, DamageKind::Melee), player);
+        app.world_mut().trigger(WaveAdvanced);
+        // Taking damage only spoils the wave it was taken in.
+        app.world_mut().trigger(WaveAdvanced);
+
+        assert_eq!(
+            app.world().resource::<Score>().flawless_waves,
+            2 * FLAWLESS_WAVE_BONUS
+        );
+    }
+
+    #[test]
+    fn time_bonus_shrinks_until_par() {
+        assert_eq!(
+            Score::time_bonus(Duration::ZERO),
+            (TIME_BONUS_PAR.as_secs_f32() * TIME_BONUS_PER_SECOND) as u32
+        );
+        assert_eq!(
+            Score::time_bonus(TIME_BONUS_PAR - Duration::from_secs(10)),
+            (10.0 * TIME_BONUS_PER_SECOND) as u32
+        );
+        assert_eq!(Score::time_bonus(TIME_BONUS_PAR), 0);
+        assert_eq!(Score::time_bonus(TIME_BONUS_PAR * 2), 0);
+    }
+}
@@ -9,13 +9,17 @@ use crate::{
     font::FontAssets,
     gameplay::{
         crosshair::CrosshairState,
-        health::Health,
+        health::{Armor, Health},
         player::{
             Player,
             default_input::{BlocksInput, OpenUpgradeMenu},
+            grenades::Grenades,
             gunplay::WeaponStats,
             movement::MovementStats,
+            weapons::{Weapon, WeaponInventory},
         },
+        replay::Replay,
+        rng::{GameplayRng, RngStream},
         waves::{WaveFinishedPreparing, WaveStartedPreparing},
     },
     screens::Screen,
@@ -23,21 +27,48 @@ use crate::{
 };
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_observer(offer_upgrades);
+    app.add_plugins(simulation_plugin);
     app.add_observer(spawn_upgrade_ui);
-    app.add_observer(unoffer_upgrades);
-    app.add_observer(despawn_upgrades);
+    app.add_observer(close_upgrade_menu);
     app.add_systems(
         Update,
         (pause_in_menu, hide_upgrade_menu_on_pause).run_if(any_with_component::<UpgradeMenu>),
     );
 }
 
+/// Offering and applying upgrades, without the upgrade menu.
+pub(crate) fn simulation_plugin(app: &mut App) {
+    app.add_observer(offer_upgrades);
+    app.add_observer(apply_upgrade);
+    app.add_observer(unoffer_upgrades);
+    app.add_observer(despawn_upgrades);
+}
+
+/// How much stronger an upgrade is when it only targets a single weapon instead of all of them.
+const SINGLE_WEAPON_UPGRADE_FACTOR: f32 = 2.0;
+/// How much armor the armor upgrade adds.
+const ARMOR_UPGRADE: f32 = 25.0;
+
 #[derive(Component, Reflect, Debug, Deref, DerefMut)]
 #[reflect(Component)]
-pub(crate) struct Upgrades(Vec<Upgrade>);
+pub(crate) struct Upgrades(Vec<OfferedUpgrade>);
 
-#[derive(Reflect, Debug, Clone, Copy)]
+/// An upgrade together with the weapons it applies to.
+#[derive(Reflect, Debug, Clone, Copy, PartialEq)]
+pub(crate) struct OfferedUpgrade {
+    pub(crate) upgrade: Upgrade,
+    /// Ignored for upgrades that do not affect weapons.
+    pub(crate) target: UpgradeTarget,
+}
+
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum UpgradeTarget {
+    AllWeapons,
+    /// Only the given weapon, with the effect multiplied by [`SINGLE_WEAPON_UPGRADE_FACTOR`].
+    Weapon(Weapon),
+}
+
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum Upgrade {
     Health,
     ShotDamage,
@@ -46,18 +77,102 @@ pub(crate) enum Upgrade {
     BulletCount,
     JumpShotPushback,
     EnemyExplosionRadius,
+    MagazineSize,
+    ReloadSpeed,
+    Grenades,
+    Armor,
 }
 impl Upgrade {
+    /// Every upgrade, in declaration order. Add new upgrades here!
+    pub(crate) const ALL: [Upgrade; 11] = [
+        Upgrade::Health,
+        Upgrade::ShotDamage,
+        Upgrade::MovementSpeed,
+        Upgrade::Accuracy,
+        Upgrade::BulletCount,
+        Upgrade::JumpShotPushback,
+        Upgrade::EnemyExplosionRadius,
+        Upgrade::MagazineSize,
+        Upgrade::ReloadSpeed,
+        Upgrade::Grenades,
+        Upgrade::Armor,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Upgrade::Health => "Heal",
+            Upgrade::ShotDamage => "Increase Shot Damage",
+            Upgrade::MovementSpeed => "Increase Movement Speed",
+            Upgrade::Accuracy => "Increase Shot Accuracy",
+            Upgrade::BulletCount => "Two More Bullets per Shot",
+            Upgrade::JumpShotPushback => "Increase Jump-Shot Pushback",
+            Upgrade::EnemyExplosionRadius => "Larger Enemy Explosion",
+            Upgrade::MagazineSize => "Two More Shells per Magazine",
+            Upgrade::ReloadSpeed => "Faster Reload",
+            Upgrade::Grenades => "Carry One More Grenade",
+            Upgrade::Armor => "More Armor",
+        }
+    }
+
+    /// Whether the upgrade affects the player's weapons, and can thus target a single weapon.
+    fn affects_weapons(self) -> bool {
+        !matches!(
+            self,
+            Upgrade::Health | Upgrade::MovementSpeed | Upgrade::Grenades | Upgrade::Armor
+        )
+    }
+
+    fn apply_to_weapon(self, stats: &mut WeaponStats, factor: f32) {
+        match self {
+            Upgrade::ShotDamage => stats.damage += 1.5 * factor,
+            Upgrade::Accuracy => {
+                stats.spread_radius = (stats.spread_radius - 0.02 * factor).max(0.0);
+            }
+            Upgrade::BulletCount => stats.pellets += (2.0 * factor) as u32,
+            Upgrade::JumpShotPushback => stats.pushback += 2.0 * factor,
+            Upgrade::EnemyExplosionRadius => stats.extra_enemy_explosion_radius += 0.1 * factor,
+            Upgrade::MagazineSize => stats.magazine_size += (2.0 * factor) as u32,
+            Upgrade::ReloadSpeed => stats.reload_rate *= 1.0 + 0.25 * factor,
+            Upgrade::Health | Upgrade::MovementSpeed | Upgrade::Grenades | Upgrade::Armor => {}
+        }
+    }
+
     fn all_except_health() -> Vec<Upgrade> {
-        // Add new upgrades here!
-        vec![
-            Upgrade::ShotDamage,
-            Upgrade::MovementSpeed,
-            Upgrade::Accuracy,
-            Upgrade::BulletCount,
-            Upgrade::JumpShotPushback,
-            Upgrade::EnemyExplosionRadius,
-        ]
+        Self::ALL
+            .into_iter()
+            .filter(|upgrade| *upgrade != Upgrade::Health)
+            .collect()
+    }
+}
+
+impl UpgradeTarget {
+    fn factor(self) -> f32 {
+        match self {
+            UpgradeTarget::AllWeapons => 1.0,
+            UpgradeTarget::Weapon(_) => SINGLE_WEAPON_UPGRADE_FACTOR,
+        }
+    }
+
+    fn includes(self, weapon: Weapon) -> bool {
+        match self {
+            UpgradeTarget::AllWeapons => true,
+            UpgradeTarget::Weapon(target) => target == weapon,
+        }
+    }
+}
+
+impl OfferedUpgrade {
+    fn label(self) -> String {
+        let label = self.upgrade.label();
+        if !self.upgrade.affects_weapons() {
+            return label.to_string();
+        }
+        match self.target {
+            UpgradeTarget::AllWeapons => format!("All Weapons: {label}"),
+            UpgradeTarget::Weapon(weapon) => {
+                format!("{}: {label} (Doubled)", weapon.definition().name)
+            }
+        }
     }
 }
 
@@ -65,13 +180,36 @@ impl Upgrade {
 #[reflect(Component)]
 struct UpgradeMenu;
 
-fn offer_upgrades(_trigger: Trigger<WaveStartedPreparing>, mut commands: Commands) {
+/// Applies an upgrade to the player and closes the upgrade menu.
+#[derive(Event, Debug, Clone, Copy)]
+pub(crate) struct ApplyUpgrade(pub(crate) OfferedUpgrade);
+
+fn offer_upgrades(
+    _trigger: Trigger<WaveStartedPreparing>,
+    mut rng: ResMut<GameplayRng>,
+    mut commands: Commands,
+) {
+    let rng = rng.stream(RngStream::Upgrades);
     let available_upgrades = Upgrade::all_except_health();
     let upgrades = available_upgrades
-        .choose_multiple(&mut rand::thread_rng(), 2)
-        .copied();
+        .choose_multiple(rng, 2)
+        .copied()
+        .collect::<Vec<_>>();
+    let targets = once(UpgradeTarget::AllWeapons)
+        .chain(Weapon::ALL.map(UpgradeTarget::Weapon))
+        .collect::<Vec<_>>();
     // Healing is always available.
-    let upgrades = once(Upgrade::Health).chain(upgrades).collect();
+    let upgrades = once(Upgrade::Health)
+        .chain(upgrades)
+        .map(|upgrade| OfferedUpgrade {
+            upgrade,
+            target: if upgrade.affects_weapons() {
+                *targets.choose(rng).unwrap()
+            } else {
+                UpgradeTarget::AllWeapons
+            },
+        })
+        .collect();
     commands.spawn((Upgrades(upgrades), StateScoped(Screen::Gameplay)));
 }
 
@@ -98,42 +236,19 @@ fn spawn_upgrade_ui(
         StateScoped(Screen::Gameplay),
         UpgradeMenu,
     ));
-    for upgrade in upgrades.iter() {
-        match upgrade {
-            Upgrade::Health => {
-                menu_commands.with_child(button("Heal", fonts.default.clone(), upgrade_health))
-            }
-            Upgrade::ShotDamage => menu_commands.with_child(button(
-                "Increase Shot Damage",
-                fonts.default.clone(),
-                upgrade_damage,
-            )),
-            Upgrade::MovementSpeed => menu_commands.with_child(button(
-                "Increase Movement Speed",
-                fonts.default.clone(),
-                upgrade_speed,
-            )),
-            Upgrade::Accuracy => menu_commands.with_child(button(
-                "Increase Shot Accuracy",
-                fonts.default.clone(),
-                upgrade_accuracy,
-            )),
-            Upgrade::BulletCount => menu_commands.with_child(button(
-                "Two More Bullets per Shot",
-                fonts.default.clone(),
-                upgrade_bullets,
-            )),
-            Upgrade::JumpShotPushback => menu_commands.with_child(button(
-                "Increase Jump-Shot Pushback",
-                fonts.default.clone(),
-                upgrade_jump_shot_pushback,
-            )),
-            Upgrade::EnemyExplosionRadius => menu_commands.with_child(button(
-                "Larger Enemy Explosion",
-                fonts.default.clone(),
-                upgrade_enemy_explosion_radius,
-            )),
-        };
+    for &upgrade in upgrades.iter() {
+        menu_commands.with_child(button(
+            upgrade.label(),
+            fonts.default.clone(),
+            move |_: Trigger<Pointer<Click>>,
+                  replay: Option<Res<Replay>>,
+                  mut commands: Commands| {
+                // Replays pick the recorded upgrades instead.
+                if replay.is_none() {
+                    commands.trigger(ApplyUpgrade(upgrade));
+                }
+            },
+        ));
     }
 }
 
@@ -148,86 +263,69 @@ fn hide_upgrade_menu_on_pause(
     }
 }
 
-fn upgrade_health(
-    _: Trigger<Pointer<Click>>,
-    mut health: Single<&mut Health, With<Player>>,
-    mut commands: Commands,
-) {
-    health.heal_full();
-    commands.trigger(DespawnUpgrades);
-}
-
-fn upgrade_speed(
-    _: Trigger<Pointer<Click>>,
-    mut movement_stats: Single<&mut MovementStats, With<Player>>,
-    mut commands: Commands,
-) {
-    movement_stats.speed_factor += 0.15;
-    commands.trigger(DespawnUpgrades);
-}
-
-fn upgrade_accuracy(
-    _: Trigger<Pointer<Click>>,
-    mut weapon_stats: Single<&mut WeaponStats, With<Player>>,
+fn apply_upgrade(
+    trigger: Trigger<ApplyUpgrade>,
+    player: Single<
+        (
+            &mut Health,
+            &mut MovementStats,
+            &mut WeaponInventory,
+            &mut Grenades,
+            &mut Armor,
+        ),
+        With<Player>,
+    >,
     mut commands: Commands,
 ) {
-    weapon_stats.spread_radius = (weapon_stats.spread_radius - 0.02).max(0.0);
-    commands.trigger(DespawnUpgrades);
-}
-
-fn upgrade_bullets(
-    _: Trigger<Pointer<Click>>,
-    mut weapon_stats: Single<&mut WeaponStats, With<Player>>,
-    mut commands: Commands,
-) {
-    weapon_stats.pellets += 2;
-    commands.trigger(DespawnUpgrades);
-}
-
-fn upgrade_damage(
-    _: Trigger<Pointer<Click>>,
-    mut weapon_stats: Single<&mut WeaponStats, With<Player>>,
-    mut commands: Commands,
-) {
-    weapon_stats.damage += 1.5;
+    let (mut health, mut movement_stats, mut inventory, mut grenades, mut armor) =
+        player.into_inner();
+    let OfferedUpgrade { upgrade, target } = trigger.0;
+    match upgrade {
+        Upgrade::Health => health.heal_full(),
+        Upgrade::MovementSpeed => movement_stats.speed_factor += 0.15,
+        Upgrade::Grenades => {
+            grenades.capacity += 1;
+            grenades.refill();
+        }
+        Upgrade::Armor => {
+            armor.max += ARMOR_UPGRADE;
+            armor.current = armor.max;
+        }
+        _ => {
+            for slot in inventory
+                .slots_mut()
+                .filter(|slot| target.includes(slot.weapon))
+            {
+                upgrade.apply_to_weapon(&mut slot.stats, target.factor());
+            }
+        }
+    }
     commands.trigger(DespawnUpgrades);
 }
 
-fn upgrade_jump_shot_pushback(
-    _: Trigger<Pointer<Click>>,
-    mut weapon_stats: Single<&mut WeaponStats, With<Player>>,
-    mut commands: Commands,
-) {
-    weapon_stats.pushback += 2.0;
+fn unoffer_upgrades(_trigger: Trigger<WaveFinishedPreparing>, mut commands: Commands) {
     commands.trigger(DespawnUpgrades);
 }
 
-fn upgrade_enemy_explosion_radius(
-    _: Trigger<Pointer<Click>>,
-    mut weapon_stats: Single<&mut WeaponStats, With<Player>>,
+fn despawn_upgrades(
+    _trigger: Trigger<DespawnUpgrades>,
     mut commands: Commands,
+    upgrades: Query<Entity, With<Upgrades>>,
 ) {
-    weapon_stats.extra_enemy_explosion_radius += 0.1;
-    commands.trigger(DespawnUpgrades);
-}
-
-fn unoffer_upgrades(_trigger: Trigger<WaveFinishedPreparing>, mut commands: Commands) {
-    commands.trigger(DespawnUpgrades);
+    for upgrade in upgrades.iter() {
+        commands.entity(upgrade).despawn();
+    }
 }
 
-fn despawn_upgrades(
+fn close_upgrade_menu(
     _trigger: Trigger<DespawnUpgrades>,
     mut commands: Commands,
-    upgrades: Query<Entity, With<Upgrades>>,
     upgrade_menus: Query<Entity, With<UpgradeMenu>>,
     mut block_input: ResMut<BlocksInput>,
     mut crosshair_state: Single<&mut CrosshairState>,
     mut time: ResMut<Time<Virtual>>,
     mut window: Single<&mut Window>,
 ) {
-    for upgrade in upgrades.iter() {
-        commands.entity(upgrade).despawn();
-    }
     for upgrade_menu in upgrade_menus.iter() {
         commands.entity(upgrade_menu).despawn();
     }
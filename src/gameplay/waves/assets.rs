@@ -0,0 +1,282 @@
+//! Data-driven wave definitions.
+//!
+//! Waves are described in `.waves.ron` files, which reference the `.packets.ron` file
+//! containing the spawn packets the waves pick from. Both are validated when loaded
+//! and hot reloaded when the `file_watcher` feature is enabled.
+//!
+//! A map picks its wave table through a [`LevelWaveTable`] entity. Maps without one
+//! use the default wave table.
+
+use anyhow::{anyhow, bail};
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+    scene::SceneInstanceReady,
+};
+use bevy_trenchbroom::prelude::*;
+use serde::Deserialize;
+
+use super::{Difficulty, SpawnPackets, Wave, Waves};
+use crate::{asset_tracking::LoadResource, screens::Screen};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<WaveTable>();
+    app.init_asset::<SpawnPackets>();
+    app.init_asset_loader::<WaveTableLoader>();
+    app.init_asset_loader::<SpawnPacketsLoader>();
+
+    app.register_type::<WaveAssets>();
+    app.load_resource::<WaveAssets>();
+
+    app.register_type::<LevelWaveTable>();
+    app.register_type::<PendingWaves>();
+
+    app.add_systems(Update, (insert_pending_waves, hot_reload_wave_tables));
+}
+
+#[derive(Resource, Asset, Clone, Reflect)]
+#[reflect(Resource)]
+pub(crate) struct WaveAssets {
+    #[dependency]
+    pub(crate) default_waves: Handle<WaveTable>,
+}
+
+impl FromWorld for WaveAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            default_waves: assets.load("waves/default.waves.ron"),
+        }
+    }
+}
+
+/// Selects the wave table used by the map this entity is placed in.
+#[derive(PointClass, Component, Debug, Reflect)]
+#[reflect(QuakeClass, Component)]
+#[base(Transform)]
+pub(crate) struct LevelWaveTable {
+    /// Path to the `.waves.ron` file, relative to the `assets` directory.
+    path: String,
+}
+
+impl Default for LevelWaveTable {
+    fn default() -> Self {
+        Self {
+            path: "waves/default.waves.ron".to_string(),
+        }
+    }
+}
+
+/// A [`Waves`] entity whose wave table is still loading.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+struct PendingWaves(Handle<WaveTable>);
+
+/// Observer for the level's [`SceneInstanceReady`] that spawns the waves of the level.
+pub(crate) fn spawn_level_waves(
+    _trigger: Trigger<SceneInstanceReady>,
+    level_wave_tables: Query<&LevelWaveTable>,
+    wave_assets: Res<WaveAssets>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    let mut level_wave_tables = level_wave_tables.iter();
+    let table = match level_wave_tables.next() {
+        Some(level_wave_table) => asset_server.load(&level_wave_table.path),
+        None => wave_assets.default_waves.clone(),
+    };
+    if level_wave_tables.next().is_some() {
+        warn!("The level contains multiple wave tables, using the first one");
+    }
+    commands.spawn((
+        Name::new("Waves"),
+        StateScoped(Screen::Gameplay),
+        PendingWaves(table),
+    ));
+}
+
+fn insert_pending_waves(
+    pending_waves: Query<(Entity, &PendingWaves)>,
+    wave_tables: Res<Assets<WaveTable>>,
+    wave_assets: Res<WaveAssets>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    for (entity, pending) in &pending_waves {
+        if let Some(table) = wave_tables.get(&pending.0) {
+            commands
+                .entity(entity)
+                .remove::<PendingWaves>()
+                .insert(Waves::new(pending.0.clone(), table));
+        } else if asset_server.load_state(&pending.0).is_failed() {
+            if pending.0 == wave_assets.default_waves {
+                error!("Failed to load the default wave table, the level has no waves");
+                commands.entity(entity).remove::<PendingWaves>();
+                continue;
+            }
+            error!("Failed to load the level's wave table, falling back to the default one");
+            commands
+                .entity(entity)
+                .insert(PendingWaves(wave_assets.default_waves.clone()));
+        }
+    }
+}
+
+/// All waves of a game, together with the spawn packets they pick from.
+#[derive(Asset, Reflect, Clone, Debug)]
+pub(crate) struct WaveTable {
+    pub(super) waves: Vec<Wave>,
+    /// The index of the first wave that endless mode picks new waves from.
+    pub(super) endless_pool_start: usize,
+    pub(super) packets: SpawnPackets,
+}
+
+impl WaveTable {
+    pub(super) fn endless_pool(&self) -> &[Wave] {
+        &self.waves[self.endless_pool_start..]
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.waves.is_empty() {
+            bail!("The wave table contains no waves");
+        }
+        if self.endless_pool_start >= self.waves.len() {
+            bail!(
+                "`endless_pool_start` is {}, but there are only {} waves",
+                self.endless_pool_start,
+                self.waves.len()
+            );
+        }
+        for (index, wave) in self.waves.iter().enumerate() {
+            for (millis, difficulty) in &wave.packet_kinds {
+                if !self.packets.has_difficulty(*difficulty) {
+                    bail!(
+                        "Wave {wave} spawns a packet of difficulty {difficulty} at {millis}, \
+                        but there are no spawn packets with that difficulty",
+                        wave = index + 1
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SpawnPackets {
+    fn has_difficulty(&self, difficulty: Difficulty) -> bool {
+        self.0.iter().any(|packet| packet.difficulty == difficulty)
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.0.is_empty() {
+            bail!("The file contains no spawn packets");
+        }
+        for (index, packet) in self.0.iter().enumerate() {
+            if packet.spawns.is_empty() {
+                bail!(
+                    "Spawn packet {packet} (difficulty {difficulty}) contains no spawns",
+                    packet = index + 1,
+                    difficulty = packet.difficulty
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The on-disk representation of a [`WaveTable`].
+#[derive(Deserialize)]
+struct WaveTableFile {
+    /// Path to the `.packets.ron` file, relative to the `.waves.ron` file.
+    packets: String,
+    waves: Vec<Wave>,
+    endless_pool_start: usize,
+}
+
+#[derive(Default)]
+struct WaveTableLoader;
+
+impl AssetLoader for WaveTableLoader {
+    type Asset = WaveTable;
+    type Settings = ();
+    type Error = anyhow::Error;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let file: WaveTableFile = ron::de::from_bytes(&bytes)?;
+
+        // Loading the packets immediately makes them a dependency of this file,
+        // so editing them hot reloads the whole wave table.
+        let packets_path = load_context.asset_path().resolve_embed(&file.packets)?;
+        let packets = load_context
+            .loader()
+            .immediate()
+            .load::<SpawnPackets>(&packets_path)
+            .await
+            .map_err(|err| anyhow!("Failed to load spawn packets \"{packets_path}\": {err}"))?
+            .take();
+
+        let table = WaveTable {
+            waves: file.waves,
+            endless_pool_start: file.endless_pool_start,
+            packets,
+        };
+        table.validate()?;
+        Ok(table)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["waves.ron"]
+    }
+}
+
+#[derive(Default)]
+struct SpawnPacketsLoader;
+
+impl AssetLoader for SpawnPacketsLoader {
+    type Asset = SpawnPackets;
+    type Settings = ();
+    type Error = anyhow::Error;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let packets: SpawnPackets = ron::de::from_bytes(&bytes)?;
+        packets.validate()?;
+        Ok(packets)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["packets.ron"]
+    }
+}
+
+fn hot_reload_wave_tables(
+    mut asset_events: EventReader<AssetEvent<WaveTable>>,
+    wave_tables: Res<Assets<WaveTable>>,
+    mut waves: Query<&mut Waves>,
+) {
+    for event in asset_events.read() {
+        let AssetEvent::Modified { id } = event else {
+            continue;
+        };
+        let Some(table) = wave_tables.get(*id) else {
+            continue;
+        };
+        for mut waves in waves.iter_mut().filter(|waves| waves.table.id() == *id) {
+            waves.reload(table);
+            info!("Reloaded wave table");
+        }
+    }
+}
@@ -0,0 +1,608 @@
+use std::time::Duration;
+
+use assets::WaveTable;
+use avian3d::prelude::*;
+use bevy::{prelude::*, time::Stopwatch};
+use rand::seq::SliceRandom as _;
+use serde::Deserialize;
+use spawner::{Spawner, SpawnerRules};
+
+use crate::{
+    PrePhysicsAppSystems,
+    gameplay::{
+        health::{Armor, Resistances},
+        npc::{
+            Npc, attack::MeleeAttack, behavior::Behavior, hit_zones::HitZoneMultipliers,
+            spit::RangedAttack, stats::NpcStats,
+        },
+        rng::{GameplayRng, RngStream},
+    },
+    props::generic::BarrelLargeClosed,
+    screens::Screen,
+    third_party::avian3d::CollisionLayer,
+};
+
+pub(crate) mod assets;
+mod spawner;
+#[cfg(test)]
+mod tests;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins((assets::plugin, simulation_plugin));
+}
+
+/// The wave logic, without loading wave tables from disk.
+pub(crate) fn simulation_plugin(app: &mut App) {
+    app.add_plugins(spawner::plugin);
+    app.register_type::<(Waves, SpawnVariant)>();
+    app.init_state::<GameMode>();
+    app.add_systems(
+        RunFixedMainLoop,
+        advance_waves
+            .in_set(PrePhysicsAppSystems::SpawnWave)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+#[derive(States, Debug, Hash, PartialEq, Eq, Clone, Default)]
+#[states(scoped_entities)]
+pub enum GameMode {
+    #[default]
+    Indeterminate,
+    Normal,
+    Endless,
+}
+
+#[derive(Event)]
+pub(crate) struct WaveAdvanced;
+
+#[derive(Event)]
+pub(crate) struct WaveWaitingForEnemies;
+
+#[derive(Event)]
+pub(crate) struct WaveStartedPreparing;
+
+#[derive(Event)]
+pub(crate) struct WaveFinishedPreparing;
+
+#[derive(Event)]
+pub(crate) struct GameWon;
+
+fn advance_waves(
+    mut waves: Single<&mut Waves>,
+    time: Res<Time>,
+    enemies: Query<(), With<Npc>>,
+    spawners: Query<(&Transform, &Spawner, &SpawnerRules)>,
+    spatial_query: SpatialQuery,
+    mut rng: ResMut<GameplayRng>,
+    mut commands: Commands,
+    game_mode: Res<State<GameMode>>,
+) {
+    let rng = rng.stream(RngStream::Waves);
+    if **game_mode == GameMode::Endless {
+        // Add a new wave selected randomly from the endless pool of the wave table.
+        if let Some(new_wave) = waves.endless_pool.choose(rng).cloned() {
+            waves.waves.push(new_wave);
+            info_once!("New wave added");
+        }
+    }
+
+    let is_preparing_before = waves.is_preparing();
+    let advancement = waves.try_advance(time.delta(), !enemies.is_empty());
+    let is_preparing_after = waves.is_preparing();
+
+    match advancement {
+        WaveAdvancement::Advanced => {
+            commands.trigger(WaveAdvanced);
+        }
+        WaveAdvancement::WaitingForEnemies => {
+            commands.trigger(WaveWaitingForEnemies);
+        }
+        WaveAdvancement::Ongoing => {}
+    }
+
+    if !is_preparing_before && is_preparing_after {
+        commands.trigger(WaveStartedPreparing);
+    }
+
+    if is_preparing_before && !is_preparing_after {
+        commands.trigger(WaveFinishedPreparing);
+    }
+
+    if waves.is_finished() {
+        if enemies.is_empty() {
+            commands.trigger(GameWon);
+        } else {
+            info_once!("Game finished, but there are still enemies");
+        }
+        return;
+    }
+    if !is_preparing_after {
+        let difficulties = waves.pop_difficulties_to_spawn();
+        for difficulty in difficulties {
+            let available_packets = waves.packets.filter_difficulty(difficulty);
+            let Some(packet) = available_packets.choose(rng) else {
+                error!("No packets available for difficulty {difficulty}");
+                continue;
+            };
+            waves.current_packets.push(packet.clone());
+        }
+        let spawns = waves
+            .current_packets
+            .iter_mut()
+            .flat_map(|packet| packet.pop_spawns())
+            .collect::<Vec<_>>();
+
+        waves.clean_finished_packets();
+        let wave_index = waves.current_wave_index();
+        let spawner_groups = waves
+            .current_wave()
+            .map(|wave| wave.spawner_groups.as_slice())
+            .unwrap_or_default();
+        let spawners = spawners
+            .iter()
+            .filter(|(_, spawner, rules)| {
+                (spawner_groups.is_empty() || spawner_groups.contains(&spawner.group))
+                    && rules.is_active_in(wave_index)
+            })
+            .collect::<Vec<_>>();
+        for spawn in spawns {
+            let eligible_spawners = spawners
+                .iter()
+                .copied()
+                .filter(|(_, _, rules)| rules.allows(spawn))
+                .collect::<Vec<_>>();
+            let Ok(&(transform, spawner, _)) =
+                eligible_spawners.choose_weighted(rng, |(_, spawner, _)| spawner.weight.max(0.0))
+            else {
+                error!(
+                    "No spawners available for {spawn:?} in wave {wave}",
+                    wave = wave_index + 1
+                );
+                continue;
+            };
+            let spawner_transform = transform.translation;
+            let spawner_radius = spawner.radius;
+            let pos2 = Circle::new(spawner_radius).sample_interior(rng);
+            let pos3 = Vec3::new(pos2.x, 0.0, pos2.y);
+            let try_spawn_position = spawner_transform + pos3;
+            let Ok(dir) = Dir3::try_from(try_spawn_position - spawner_transform) else {
+                error!("Invalid direction, skipping spawn");
+                continue;
+            };
+            let filter = SpatialQueryFilter::default().with_mask([CollisionLayer::Default]);
+            let spawn_position = if let Some(hit) =
+                spatial_query.cast_ray(spawner_transform, dir, pos3.length(), true, &filter)
+            {
+                spawner_transform + dir * (hit.distance - 1.0).max(0.0)
+            } else {
+                try_spawn_position
+            };
+            let mut spawn_commands = commands.spawn((
+                spawn,
+                Visibility::Inherited,
+                Transform::from_translation(spawn_position),
+            ));
+            let buff_i = waves.current_wave_index().saturating_sub(5) / 5;
+            let scale_stat = move |base_stat: f32, factor: f32| -> f32 {
+                base_stat * (1.0 + factor * buff_i as f32)
+            };
+            match spawn {
+                SpawnVariant::BasicEnemy => {
+                    spawn_commands.insert((
+                        Name::new("Basic Enemy"),
+                        Npc,
+                        NpcStats {
+                            health: scale_stat(100.0, 0.1),
+                            desired_speed: scale_stat(7.0, 0.1),
+                            max_speed: scale_stat(8.0, 0.1),
+                            attack_damage: scale_stat(10.0, 0.05),
+                            attack_speed_range: scale_stat(1.5, 0.1)..scale_stat(2.3, 0.1),
+                            attack: MeleeAttack::default(),
+                            ranged: None,
+                            behavior: Behavior::melee(),
+                            // Basic enemies are dumb enough to be baited into barrels.
+                            danger_cost: 1.0,
+                            size: 1.0,
+                            stagger_chance: 0.3,
+                            stagger_duration: (0.2 * (1.0 - (buff_i as f32 * 0.05).min(0.5)))
+                                ..(0.4 * (1.0 - (buff_i as f32 * 0.05).min(0.5))),
+                            hit_zones: HitZoneMultipliers::default(),
+                            armor: None,
+                            shield: None,
+                            resistances: Resistances::default(),
+                        },
+                    ));
+                }
+                SpawnVariant::BigEnemy => {
+                    spawn_commands.insert((
+                        Name::new("Big Enemy"),
+                        Npc,
+                        NpcStats {
+                            health: scale_stat(400.0, 0.1),
+                            desired_speed: scale_stat(5.0, 0.1),
+                            max_speed: scale_stat(5.0, 0.1),
+                            attack_damage: scale_stat(40.0, 0.05),
+                            attack_speed_range: scale_stat(1.1, 0.1)..scale_stat(1.7, 0.1),
+                            // A slow, heavy swing that sends the player flying.
+                            attack: MeleeAttack {
+                                hitbox_size: Vec3::splat(3.0),
+                                hitbox_offset: Vec3::new(0.0, 0.0, -2.0),
+                                hit_frames: 24..31,
+                                knockback: 12.0,
+                                stagger_secs: Some(0.4),
+                            },
+                            ranged: None,
+                            behavior: Behavior::melee(),
+                            // Big enemies are slow, but smart enough to walk around barrels.
+                            danger_cost: 4.0,
+                            size: 2.0,
+                            stagger_chance: 0.2,
+                            stagger_duration: (0.1 * (1.0 - (buff_i as f32 * 0.05).min(0.5)))
+                                ..(0.3 * (1.0 - (buff_i as f32 * 0.05).min(0.5))),
+                            // Big enemies shrug off hits to their limbs, so aim for the head.
+                            hit_zones: HitZoneMultipliers {
+                                head: 3.0,
+                                torso: 1.0,
+                                arm: 0.4,
+                                leg: 0.4,
+                            },
+                            // Big enemies are armored and barely flinch from explosions.
+                            armor: Some(Armor::new(scale_stat(150.0, 0.1), 0.5)),
+                            shield: None,
+                            resistances: Resistances {
+                                explosion: 0.3,
+                                ..default()
+                            },
+                        },
+                    ));
+                }
+                SpawnVariant::SmallEnemy => {
+                    spawn_commands.insert((
+                        Name::new("Small Enemy"),
+                        Npc,
+                        NpcStats {
+                            health: scale_stat(30.0, 0.1),
+                            desired_speed: scale_stat(11.0, 0.1),
+                            max_speed: scale_stat(11.0, 0.1),
+                            attack_damage: scale_stat(10.0, 0.05),
+                            attack_speed_range: scale_stat(2.1, 0.1)..scale_stat(2.8, 0.1),
+                            // Quick, short-reaching nibbles.
+                            attack: MeleeAttack {
+                                hitbox_size: Vec3::splat(1.1),
+                                hitbox_offset: Vec3::new(0.0, 0.0, -1.2),
+                                hit_frames: 24..28,
+                                knockback: 2.0,
+                                stagger_secs: None,
+                            },
+                            ranged: None,
+                            behavior: Behavior::melee(),
+                            danger_cost: 1.0,
+                            size: 0.7,
+                            stagger_chance: 0.5,
+                            stagger_duration: (0.2 * (1.0 - (buff_i as f32 * 0.05).min(0.5)))
+                                ..(0.3 * (1.0 - (buff_i as f32 * 0.05).min(0.5))),
+                            // Small enemies are frail everywhere.
+                            hit_zones: HitZoneMultipliers {
+                                head: 2.0,
+                                torso: 1.0,
+                                arm: 0.9,
+                                leg: 0.9,
+                            },
+                            armor: None,
+                            shield: None,
+                            resistances: Resistances::default(),
+                        },
+                    ));
+                }
+                SpawnVariant::Spitter => {
+                    spawn_commands.insert((
+                        Name::new("Spitter"),
+                        Npc,
+                        NpcStats {
+                            health: scale_stat(60.0, 0.1),
+                            desired_speed: scale_stat(6.0, 0.1),
+                            max_speed: scale_stat(7.0, 0.1),
+                            // The damage of a direct hit with the spit.
+                            attack_damage: scale_stat(15.0, 0.05),
+                            attack_speed_range: scale_stat(1.5, 0.1)..scale_stat(2.3, 0.1),
+                            attack: MeleeAttack::default(),
+                            ranged: Some(RangedAttack {
+                                preferred_distance: 14.0,
+                                min_distance: 6.0,
+                                aim_secs: 0.8,
+                                cooldown_secs: scale_stat(3.0, -0.05).max(1.5),
+                                projectile_speed: 14.0,
+                                puddle_damage: scale_stat(4.0, 0.05),
+                                puddle_secs: 5.0,
+                            }),
+                            behavior: Behavior::ranged(),
+                            // Spitters hang back and would rather not stand next to a barrel.
+                            danger_cost: 8.0,
+                            size: 0.9,
+                            stagger_chance: 0.6,
+                            stagger_duration: (0.3 * (1.0 - (buff_i as f32 * 0.05).min(0.5)))
+                                ..(0.5 * (1.0 - (buff_i as f32 * 0.05).min(0.5))),
+                            hit_zones: HitZoneMultipliers::default(),
+                            armor: None,
+                            shield: None,
+                            resistances: Resistances {
+                                acid: 1.0,
+                                ..default()
+                            },
+                        },
+                    ));
+                }
+                SpawnVariant::ExplosiveBarrel => {
+                    spawn_commands
+                        .insert((Name::new("Explosive Barrel"), BarrelLargeClosed::default()));
+                }
+            }
+        }
+    }
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub(crate) struct Waves {
+    /// The wave table these waves were created from. Used for hot reloading.
+    table: Handle<WaveTable>,
+    waves: Vec<Wave>,
+    endless_pool: Vec<Wave>,
+    packets: SpawnPackets,
+    current_packets: Vec<SpawnPacket>,
+    wave_stopwatch: Stopwatch,
+    current_wave: usize,
+    total_waves: usize,
+    prep_timer: Timer,
+}
+
+enum WaveAdvancement {
+    Advanced,
+    WaitingForEnemies,
+    Ongoing,
+}
+
+impl Waves {
+    pub(crate) fn new(table_handle: Handle<WaveTable>, table: &WaveTable) -> Self {
+        let waves = table.waves.clone();
+        let len = waves.len();
+        Self {
+            table: table_handle,
+            waves,
+            endless_pool: table.endless_pool().to_vec(),
+            packets: table.packets.clone(),
+            current_packets: Vec::new(),
+            wave_stopwatch: Stopwatch::default(),
+            current_wave: 0,
+            total_waves: len,
+            prep_timer: Timer::from_seconds(0.0, TimerMode::Once),
+        }
+    }
+
+    /// Applies a modified wave table. Waves that have already started are left untouched,
+    /// so that hot reloading does not disrupt the wave currently being played.
+    fn reload(&mut self, table: &WaveTable) {
+        let upcoming = self.current_wave + 1;
+        self.waves.truncate(upcoming);
+        self.waves
+            .extend(table.waves.iter().skip(upcoming).cloned());
+        self.total_waves = table.waves.len();
+        self.endless_pool = table.endless_pool().to_vec();
+        self.packets = table.packets.clone();
+    }
+
+    pub(crate) fn current_wave_index(&self) -> usize {
+        self.current_wave
+    }
+
+    pub(crate) fn total_waves(&self) -> usize {
+        self.total_waves
+    }
+
+    pub(crate) fn prep_time_left(&self) -> Duration {
+        self.prep_timer.remaining()
+    }
+
+    pub(crate) fn prep_timer_elapsed(&self) -> Duration {
+        self.prep_timer.elapsed()
+    }
+
+    fn try_advance(&mut self, delta: Duration, has_enemies: bool) -> WaveAdvancement {
+        let mut advancement = WaveAdvancement::Ongoing;
+        if !self.is_finished()
+            && self
+                .current_wave()
+                .map(|wave| wave.packet_kinds.is_empty())
+                .unwrap_or(false)
+        {
+            if has_enemies {
+                advancement = WaveAdvancement::WaitingForEnemies;
+            } else {
+                self.advance_wave();
+                advancement = WaveAdvancement::Advanced;
+            }
+        }
+        if self.is_preparing() {
+            self.prep_timer.tick(delta);
+        } else {
+            self.wave_stopwatch.tick(delta);
+            for packet in self.current_packets.iter_mut() {
+                packet.tick(delta);
+            }
+        }
+        advancement
+    }
+
+    fn clean_finished_packets(&mut self) {
+        self.current_packets
+            .retain(|packet| !packet.spawns.is_empty());
+    }
+
+    fn current_wave(&self) -> Option<&Wave> {
+        self.waves.get(self.current_wave)
+    }
+
+    fn current_wave_mut(&mut self) -> Option<&mut Wave> {
+        self.waves.get_mut(self.current_wave)
+    }
+
+    fn elapsed_millis(&self) -> Millis {
+        self.wave_stopwatch.elapsed().into()
+    }
+
+    fn pop_difficulties_to_spawn(&mut self) -> Vec<Difficulty> {
+        let mut difficulties = Vec::new();
+
+        let elapsed = self.elapsed_millis();
+        let Some(current_wave) = self.current_wave() else {
+            return difficulties;
+        };
+        for (millis, difficulty) in current_wave.packet_kinds_ordered() {
+            if elapsed > millis {
+                difficulties.push(difficulty);
+                self.current_wave_mut()
+                    .unwrap()
+                    .packet_kinds
+                    .retain(|(m, _)| *m != millis);
+            }
+        }
+        difficulties
+    }
+
+    pub(crate) fn is_preparing(&self) -> bool {
+        !self.prep_timer.finished()
+    }
+
+    fn advance_wave(&mut self) {
+        self.current_wave += 1;
+        let prep_time = if let Some(current_wave) = self.current_wave() {
+            current_wave.prep_time
+        } else {
+            Millis(0)
+        };
+        self.prep_timer = Timer::new(Duration::from_millis(prep_time.0), TimerMode::Once);
+        self.wave_stopwatch.reset();
+    }
+
+    fn is_finished(&self) -> bool {
+        self.current_wave >= self.waves.len() && self.current_packets.is_empty()
+    }
+}
+
+#[derive(Reflect, Clone, Debug, Deserialize)]
+struct Wave {
+    prep_time: Millis,
+    packet_kinds: Vec<(Millis, Difficulty)>,
+    /// The [`Spawner`] groups this wave spawns enemies from. Empty means all spawners.
+    #[serde(default)]
+    spawner_groups: Vec<String>,
+}
+
+impl Wave {
+    fn packet_kinds_ordered(&self) -> Vec<(Millis, Difficulty)> {
+        let mut vector = self
+            .packet_kinds
+            .iter()
+            .map(|(millis, difficulty)| (*millis, *difficulty))
+            .collect::<Vec<_>>();
+        vector.sort_by_key(|(millis, _)| *millis);
+        vector
+    }
+}
+
+#[derive(
+    Deref, DerefMut, Hash, PartialEq, Eq, PartialOrd, Ord, Reflect, Copy, Clone, Debug, Deserialize,
+)]
+#[serde(transparent)]
+struct Millis(u64);
+
+impl std::fmt::Display for Millis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ms", self.0)
+    }
+}
+
+impl From<Duration> for Millis {
+    fn from(duration: Duration) -> Self {
+        let millis = duration.as_millis();
+        if millis > u64::MAX as u128 {
+            error!("Duration too long to convert to Millis");
+            Millis(u64::MAX)
+        } else {
+            Self(millis as u64)
+        }
+    }
+}
+
+#[derive(
+    Deref, DerefMut, Hash, PartialEq, Eq, PartialOrd, Ord, Reflect, Copy, Clone, Debug, Deserialize,
+)]
+#[serde(transparent)]
+struct Difficulty(u32);
+
+impl std::fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Asset, Reflect, Clone, Debug, Deserialize)]
+#[serde(transparent)]
+struct SpawnPackets(Vec<SpawnPacket>);
+
+impl SpawnPackets {
+    fn filter_difficulty(&self, difficulty: Difficulty) -> Vec<SpawnPacket> {
+        self.0
+            .iter()
+            .filter(|packet| packet.difficulty == difficulty)
+            .cloned()
+            .collect()
+    }
+}
+
+#[derive(Reflect, Clone, Debug, Deserialize)]
+struct SpawnPacket {
+    difficulty: Difficulty,
+    #[serde(skip)]
+    stopwatch: Stopwatch,
+    spawns: Vec<(Millis, SpawnVariant)>,
+}
+
+impl SpawnPacket {
+    fn pop_spawns(&mut self) -> Vec<SpawnVariant> {
+        let mut spawns = Vec::new();
+        for (millis, spawn_variant) in self
+            .spawns
+            .iter()
+            .map(|(millis, spawn_variant)| (*millis, *spawn_variant))
+            .collect::<Vec<_>>()
+        {
+            if self.elapsed_millis() > millis {
+                spawns.push(spawn_variant);
+                self.spawns.retain(|(m, _)| *m != millis);
+            }
+        }
+        spawns
+    }
+
+    fn tick(&mut self, delta: Duration) {
+        self.stopwatch.tick(delta);
+    }
+
+    fn elapsed_millis(&self) -> Millis {
+        self.stopwatch.elapsed().into()
+    }
+}
+
+/// What a wave spawns. Also a component on the spawned entities.
+#[derive(Component, Reflect, Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+#[reflect(Component)]
+pub(crate) enum SpawnVariant {
+    BasicEnemy,
+    BigEnemy,
+    SmallEnemy,
+    /// A ranged enemy that keeps its distance and spits acid.
+    Spitter,
+    ExplosiveBarrel,
+}
@@ -0,0 +1,139 @@
+//! Spawners placed in TrenchBroom, along with the rules that decide which of them are used.
+
+use std::ops::RangeInclusive;
+
+use anyhow::{Context as _, bail};
+use bevy::prelude::*;
+use bevy_trenchbroom::prelude::*;
+
+use super::SpawnVariant;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Spawner>();
+    app.register_type::<SpawnerRules>();
+    app.add_observer(parse_spawner_rules);
+}
+
+/// A location enemies are spawned around.
+#[derive(PointClass, Component, Debug, Reflect)]
+#[reflect(QuakeClass, Component)]
+#[base(Transform, Visibility)]
+#[model("models/gizmo/spawner.gltf")]
+pub(super) struct Spawner {
+    pub(super) radius: f32,
+    /// The group this spawner belongs to. Waves that list `spawner_groups` only use spawners of those groups.
+    pub(super) group: String,
+    /// How likely this spawner is to be picked, relative to the other spawners that are allowed to spawn.
+    pub(super) weight: f32,
+    /// The waves this spawner is active in, e.g. `1-3, 5, 8-`. Leave empty to use it in all waves.
+    waves: String,
+    /// The enemies this spawner may spawn, e.g. `BasicEnemy, SmallEnemy`. Leave empty to allow all of them.
+    variants: String,
+}
+
+impl Default for Spawner {
+    fn default() -> Self {
+        Self {
+            radius: 5.0,
+            group: String::new(),
+            weight: 1.0,
+            waves: String::new(),
+            variants: String::new(),
+        }
+    }
+}
+
+/// The parsed `waves` and `variants` properties of a [`Spawner`].
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub(super) struct SpawnerRules {
+    /// Zero-based wave indices. `None` means every wave.
+    waves: Option<Vec<RangeInclusive<usize>>>,
+    /// `None` means every variant.
+    variants: Option<Vec<SpawnVariant>>,
+}
+
+impl SpawnerRules {
+    pub(super) fn is_active_in(&self, wave_index: usize) -> bool {
+        self.waves
+            .as_ref()
+            .is_none_or(|waves| waves.iter().any(|range| range.contains(&wave_index)))
+    }
+
+    pub(super) fn allows(&self, variant: SpawnVariant) -> bool {
+        self.variants
+            .as_ref()
+            .is_none_or(|variants| variants.contains(&variant))
+    }
+}
+
+fn parse_spawner_rules(
+    trigger: Trigger<OnAdd, Spawner>,
+    spawners: Query<(&Spawner, &Transform)>,
+    mut commands: Commands,
+) {
+    let entity = trigger.target();
+    let Ok((spawner, transform)) = spawners.get(entity) else {
+        error!("Spawner entity {entity} has no transform");
+        return;
+    };
+    let position = transform.translation;
+
+    let waves = parse_waves(&spawner.waves).unwrap_or_else(|err| {
+        error!("Spawner at {position} has invalid `waves`, using it in all waves: {err:#}");
+        None
+    });
+    let variants = parse_variants(&spawner.variants).unwrap_or_else(|err| {
+        error!("Spawner at {position} has invalid `variants`, allowing all of them: {err:#}");
+        None
+    });
+    commands
+        .entity(entity)
+        .insert(SpawnerRules { waves, variants });
+}
+
+/// Parses a comma separated list of one-based waves and wave ranges, such as `1-3, 5, 8-`.
+fn parse_waves(text: &str) -> anyhow::Result<Option<Vec<RangeInclusive<usize>>>> {
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    let parse_wave = |wave: &str| -> anyhow::Result<usize> {
+        let wave = wave
+            .parse::<usize>()
+            .with_context(|| format!("\"{wave}\" is not a wave number"))?;
+        if wave == 0 {
+            bail!("Waves start at 1");
+        }
+        Ok(wave - 1)
+    };
+    text.split(',')
+        .map(|part| {
+            let part = part.trim();
+            let range = match part.split_once('-') {
+                Some((start, "")) => parse_wave(start.trim())?..=usize::MAX,
+                Some((start, end)) => parse_wave(start.trim())?..=parse_wave(end.trim())?,
+                None => parse_wave(part)?..=parse_wave(part)?,
+            };
+            if range.is_empty() {
+                bail!("The range \"{part}\" contains no waves");
+            }
+            Ok(range)
+        })
+        .collect::<anyhow::Result<Vec<_>>>()
+        .map(Some)
+}
+
+/// Parses a comma separated list of [`SpawnVariant`]s, using the same names as the wave files.
+fn parse_variants(text: &str) -> anyhow::Result<Option<Vec<SpawnVariant>>> {
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    text.split(',')
+        .map(|variant| {
+            let variant = variant.trim();
+            ron::from_str::<SpawnVariant>(variant)
+                .with_context(|| format!("\"{variant}\" is not an enemy variant"))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()
+        .map(Some)
+}
@@ -0,0 +1,82 @@
+use bevy::{prelude::*, time::Stopwatch};
+
+use super::{
+    Difficulty, GameWon, Millis, SpawnPacket, SpawnPackets, SpawnVariant, Wave, WaveAdvanced,
+    Waves, assets::WaveTable, spawner::Spawner,
+};
+use crate::{
+    gameplay::npc::Npc,
+    headless::{headless_app, run_updates},
+};
+
+#[derive(Resource, Default)]
+struct WaveEvents {
+    advanced: usize,
+    won: usize,
+}
+
+/// A table of two waves that each spawn a single basic enemy right away.
+fn two_wave_table() -> WaveTable {
+    let wave = Wave {
+        prep_time: Millis(0),
+        packet_kinds: vec![(Millis(0), Difficulty(0))],
+        spawner_groups: Vec::new(),
+    };
+    WaveTable {
+        waves: vec![wave.clone(), wave],
+        endless_pool_start: 0,
+        packets: SpawnPackets(vec![SpawnPacket {
+            difficulty: Difficulty(0),
+            stopwatch: Stopwatch::default(),
+            spawns: vec![(Millis(0), SpawnVariant::BasicEnemy)],
+        }]),
+    }
+}
+
+#[test]
+fn game_is_won_after_the_last_wave() {
+    let mut app = headless_app();
+    app.init_resource::<WaveEvents>();
+    app.add_observer(
+        |_trigger: Trigger<WaveAdvanced>, mut events: ResMut<WaveEvents>| {
+            events.advanced += 1;
+        },
+    );
+    app.add_observer(
+        |_trigger: Trigger<GameWon>, mut events: ResMut<WaveEvents>| {
+            events.won += 1;
+        },
+    );
+
+    let table = two_wave_table();
+    app.world_mut()
+        .spawn((Spawner::default(), Transform::default()));
+    app.world_mut().spawn(Waves::new(Handle::default(), &table));
+
+    let mut spawned_enemies = 0;
+    for _ in 0..1000 {
+        run_updates(&mut app, 1);
+
+        let events = app.world().resource::<WaveEvents>();
+        if events.won > 0 {
+            break;
+        }
+        assert!(
+            events.advanced < table.waves.len(),
+            "All waves are over, but the game was not won"
+        );
+
+        // Kill every enemy right away, so that the waves advance as fast as possible.
+        let mut enemies = app.world_mut().query_filtered::<Entity, With<Npc>>();
+        let enemies = enemies.iter(app.world()).collect::<Vec<_>>();
+        spawned_enemies += enemies.len();
+        for enemy in enemies {
+            app.world_mut().despawn(enemy);
+        }
+    }
+
+    let events = app.world().resource::<WaveEvents>();
+    assert!(events.won > 0, "The game was never won");
+    assert_eq!(events.advanced, table.waves.len());
+    assert_eq!(spawned_enemies, table.waves.len());
+}
@@ -0,0 +1,86 @@
+//! A headless [`App`] for simulating gameplay in tests.
+//!
+//! It contains the gameplay logic and physics, but no rendering, audio, windowing or input.
+//! Time advances by a fixed [`TIMESTEP`] every update, so that every update runs exactly one
+//! fixed timestep and simulations are reproducible.
+
+use std::time::Duration;
+
+use avian3d::prelude::*;
+use bevy::{prelude::*, scene::ScenePlugin, state::app::StatesPlugin, time::TimeUpdateStrategy};
+
+use crate::{
+    auto_timer, despawn_after,
+    gameplay::{combo, explosion, health, npc, rng, upgrades, waves},
+    props::generic::BarrelLargeClosed,
+    screens::Screen,
+};
+
+/// The time that passes during a single update. Matches Bevy's default fixed timestep.
+pub(crate) const TIMESTEP: Duration = Duration::from_micros(15_625);
+
+/// The seed of the gameplay RNG in headless apps.
+pub(crate) const SEED: u64 = 0;
+
+/// Builds a headless app that starts in [`Screen::Gameplay`].
+pub(crate) fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        StatesPlugin,
+        TransformPlugin,
+        AssetPlugin::default(),
+        ScenePlugin,
+        PhysicsPlugins::default(),
+    ));
+    // Collider constructors and the despawn logic look up meshes and materials.
+    app.init_asset::<Mesh>();
+    app.init_asset::<StandardMaterial>();
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(TIMESTEP));
+
+    app.insert_state(Screen::Gameplay);
+    app.add_plugins((
+        auto_timer::plugin,
+        combo::plugin,
+        despawn_after::plugin,
+        health::plugin,
+        explosion::simulation_plugin,
+        npc::stats::plugin,
+        rng::plugin,
+        upgrades::simulation_plugin,
+        waves::simulation_plugin,
+    ));
+    app.insert_resource(rng::RequestedSeed(Some(SEED)));
+    app
+}
+
+/// Runs the given number of updates.
+pub(crate) fn run_updates(app: &mut App, updates: usize) {
+    for _ in 0..updates {
+        app.update();
+    }
+}
+
+/// Runs updates until the given amount of simulated time has passed.
+pub(crate) fn run_for(app: &mut App, duration: Duration) {
+    let updates = duration.as_nanos().div_ceil(TIMESTEP.as_nanos());
+    run_updates(app, updates as usize);
+}
+
+/// Spawns `count` explosive barrels in a row along the X axis, `spacing` apart, starting at the origin.
+/// Their models are not loaded in headless apps, so they get a collider of about the right size instead.
+/// Physics only picks up the colliders after a couple of updates.
+pub(crate) fn spawn_barrel_row(app: &mut App, count: usize, spacing: f32) -> Vec<Entity> {
+    (0..count)
+        .map(|i| {
+            app.world_mut()
+                .spawn((
+                    BarrelLargeClosed::default(),
+                    RigidBody::Static,
+                    Collider::cylinder(0.5, 1.2),
+                    Transform::from_xyz(i as f32 * spacing, 0.0, 0.0),
+                ))
+                .id()
+        })
+        .collect()
+}
@@ -13,7 +13,10 @@ mod fixed_update_inspection;
 mod font;
 mod gameplay;
 mod hdr;
+#[cfg(test)]
+mod headless;
 mod menus;
+mod persistence;
 mod props;
 mod screens;
 mod shader_compilation;
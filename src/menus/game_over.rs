@@ -5,11 +5,15 @@ use bevy::prelude::*;
 use crate::{
     font::FontAssets,
     gameplay::{
+        combo::Chains,
         crosshair::CrosshairState,
         health::OnDeath,
         player::{Player, default_input::BlocksInput},
+        rng::GameplayRng,
+        score::RunSubmission,
         time::GameplayTime,
     },
+    menus::leaderboard::{leaderboard_table, score_text},
     screens::Screen,
     theme::widget,
 };
@@ -29,6 +33,9 @@ fn on_player_death(
     mut block_input: ResMut<BlocksInput>,
     fonts: Res<FontAssets>,
     gameplay_time: Res<GameplayTime>,
+    rng: Res<GameplayRng>,
+    chains: Res<Chains>,
+    mut run: RunSubmission,
     mut commands: Commands,
     mut window: Single<&mut Window>,
 ) {
@@ -40,6 +47,8 @@ fn on_player_death(
     let minutes = (elapsed_secs / 60.0) as u32;
     let seconds = (elapsed_secs % 60.0) as u32;
     let milliseconds = (elapsed_secs * 1000.0) as u32 % 1000;
+    let longest_chain = chains.longest();
+    let finished_run = run.submit(false);
     commands.spawn((
         widget::ui_root("Game Over Menu"),
         StateScoped(Screen::Gameplay),
@@ -50,6 +59,16 @@ fn on_player_death(
                 format!("Time: {minutes:02}:{seconds:02}.{milliseconds:03}"),
                 fonts.default.clone()
             ),
+            widget::label(
+                format!(
+                    "Longest chain: {} explosions, {} kills",
+                    longest_chain.explosions, longest_chain.kills
+                ),
+                fonts.default.clone()
+            ),
+            widget::label(format!("Seed: {}", rng.seed()), fonts.default.clone()),
+            widget::label(score_text(&finished_run), fonts.default.clone()),
+            leaderboard_table(run.entries(), finished_run.rank, fonts.default.clone()),
             widget::button("Try Again", fonts.default.clone(), try_again),
             widget::button("Quit to Title", fonts.default.clone(), quit_to_title),
         ],
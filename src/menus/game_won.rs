@@ -6,10 +6,13 @@ use crate::{
     audio::Music,
     font::FontAssets,
     gameplay::{
-        crosshair::CrosshairState, player::default_input::BlocksInput, time::GameplayTime,
-        waves::GameWon,
+        combo::Chains, crosshair::CrosshairState, player::default_input::BlocksInput,
+        rng::GameplayRng, score::RunSubmission, time::GameplayTime, waves::GameWon,
+    },
+    menus::{
+        assets::MenuAssets,
+        leaderboard::{leaderboard_table, score_text},
     },
-    menus::assets::MenuAssets,
     screens::Screen,
     theme::widget,
 };
@@ -38,6 +41,9 @@ fn on_game_won(
     mut commands: Commands,
     game_won_marker: Query<(), With<GameWonMarker>>,
     gameplay_time: Res<GameplayTime>,
+    rng: Res<GameplayRng>,
+    chains: Res<Chains>,
+    mut run: RunSubmission,
     mut window: Single<&mut Window>,
 ) {
     if !game_won_marker.is_empty() {
@@ -48,6 +54,8 @@ fn on_game_won(
     let minutes = (elapsed_secs / 60.0) as u32;
     let seconds = (elapsed_secs % 60.0) as u32;
     let milliseconds = (elapsed_secs * 1000.0) as u32 % 1000;
+    let longest_chain = chains.longest();
+    let finished_run = run.submit(true);
     commands.spawn((GameWonMarker, StateScoped(Screen::Gameplay)));
     commands.spawn((
         widget::ui_root("Game Won Menu"),
@@ -62,6 +70,16 @@ fn on_game_won(
                 format!("Time: {minutes:02}:{seconds:02}.{milliseconds:03}"),
                 fonts.default.clone()
             ),
+            widget::label(
+                format!(
+                    "Longest chain: {} explosions, {} kills",
+                    longest_chain.explosions, longest_chain.kills
+                ),
+                fonts.default.clone()
+            ),
+            widget::label(format!("Seed: {}", rng.seed()), fonts.default.clone()),
+            widget::label(score_text(&finished_run), fonts.default.clone()),
+            leaderboard_table(run.entries(), finished_run.rank, fonts.default.clone()),
             widget::button("Quit to Title", fonts.default.clone(), quit_to_title),
         ],
     ));
@@ -0,0 +1,166 @@
+//! The leaderboard menu, and the leaderboard table shown after a run.
+
+use bevy::{
+    ecs::spawn::SpawnIter, input::common_conditions::input_just_pressed, prelude::*, ui::Val::*,
+};
+
+use crate::{
+    font::FontAssets,
+    gameplay::{
+        score::{
+            FinishedRun,
+            leaderboard::{Leaderboard, LeaderboardEntry},
+        },
+        waves::GameMode,
+    },
+    menus::Menu,
+    theme::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::Leaderboard), spawn_leaderboard_menu);
+    app.add_systems(
+        Update,
+        go_back.run_if(in_state(Menu::Leaderboard).and(input_just_pressed(KeyCode::Escape))),
+    );
+}
+
+fn spawn_leaderboard_menu(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    leaderboard: Res<Leaderboard>,
+) {
+    commands.spawn((
+        widget::ui_root("Leaderboard Menu"),
+        GlobalZIndex(2),
+        StateScoped(Menu::Leaderboard),
+        children![
+            widget::header("Leaderboard", fonts.default.clone()),
+            (
+                Name::new("Leaderboards"),
+                Node {
+                    column_gap: Px(60.0),
+                    ..default()
+                },
+                children![
+                    (
+                        Node {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            row_gap: Px(10.0),
+                            ..default()
+                        },
+                        children![
+                            widget::label("Normal", fonts.default.clone()),
+                            leaderboard_table(
+                                leaderboard.entries(&GameMode::Normal),
+                                None,
+                                fonts.default.clone()
+                            ),
+                        ],
+                    ),
+                    (
+                        Node {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            row_gap: Px(10.0),
+                            ..default()
+                        },
+                        children![
+                            widget::label("Endless", fonts.default.clone()),
+                            leaderboard_table(
+                                leaderboard.entries(&GameMode::Endless),
+                                None,
+                                fonts.default.clone()
+                            ),
+                        ],
+                    ),
+                ],
+            ),
+            widget::button("Back", fonts.default.clone(), go_back_on_click),
+        ],
+    ));
+}
+
+/// A table of leaderboard entries. The entry at `highlight` is marked as the run that was just finished.
+pub(crate) fn leaderboard_table(
+    entries: &[LeaderboardEntry],
+    highlight: Option<usize>,
+    font: Handle<Font>,
+) -> impl Bundle {
+    const COLUMNS: [&str; 5] = ["Rank", "Score", "Wave", "Time", "Chain"];
+    let rows = if entries.is_empty() {
+        vec![[
+            "-".to_string(),
+            "No runs yet".to_string(),
+            default(),
+            default(),
+            default(),
+        ]]
+    } else {
+        entries
+            .iter()
+            .enumerate()
+            .map(|(rank, entry)| {
+                let minutes = (entry.time_secs / 60.0) as u32;
+                let seconds = (entry.time_secs % 60.0) as u32;
+                [
+                    if highlight == Some(rank) {
+                        format!("> {}", rank + 1)
+                    } else {
+                        (rank + 1).to_string()
+                    },
+                    entry.score.to_string(),
+                    entry.waves.to_string(),
+                    format!("{minutes:02}:{seconds:02}"),
+                    entry.longest_chain.to_string(),
+                ]
+            })
+            .collect()
+    };
+    let cells = COLUMNS
+        .map(str::to_string)
+        .into_iter()
+        .chain(rows.into_iter().flatten())
+        .collect::<Vec<_>>();
+
+    (
+        Name::new("Leaderboard Table"),
+        Node {
+            display: Display::Grid,
+            row_gap: Px(5.0),
+            column_gap: Px(20.0),
+            grid_template_columns: RepeatedGridTrack::max_content(COLUMNS.len() as u16),
+            ..default()
+        },
+        Children::spawn(SpawnIter(cells.into_iter().map(move |text| {
+            (
+                widget::label_small(text, font.clone()),
+                Node {
+                    justify_self: JustifySelf::End,
+                    ..default()
+                },
+            )
+        }))),
+    )
+}
+
+fn go_back_on_click(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Main);
+}
+
+fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Main);
+}
+
+/// A summary of the score of a finished run.
+pub(crate) fn score_text(run: &FinishedRun) -> String {
+    let mut text = format!("Score: {}", run.score);
+    if run.time_bonus > 0 {
+        text += &format!(" (time bonus: {})", run.time_bonus);
+    }
+    if let Some(rank) = run.rank {
+        text += &format!(" - #{} on the leaderboard!", rank + 1);
+    }
+    text
+}
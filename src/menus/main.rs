@@ -3,7 +3,11 @@
 use bevy::prelude::*;
 
 use crate::{
-    font::FontAssets, gameplay::waves::GameMode, menus::Menu, screens::Screen, theme::widget,
+    font::FontAssets,
+    gameplay::{replay::watch_last_replay, waves::GameMode},
+    menus::Menu,
+    screens::Screen,
+    theme::widget,
 };
 
 pub(super) fn plugin(app: &mut App) {
@@ -27,6 +31,8 @@ fn spawn_main_menu(mut commands: Commands, fonts: Res<FontAssets>) {
                 fonts.default.clone(),
                 enter_loading_screen_endless
             ),
+            widget::button("Watch Last Run", fonts.default.clone(), watch_last_replay),
+            widget::button("Leaderboard", fonts.default.clone(), open_leaderboard_menu),
             widget::button("Settings", fonts.default.clone(), open_settings_menu),
             widget::button("Credits", fonts.default.clone(), open_credits_menu),
             widget::button("Exit", fonts.default.clone(), exit_app),
@@ -43,6 +49,8 @@ fn spawn_main_menu(mut commands: Commands, fonts: Res<FontAssets>) {
                 fonts.default.clone(),
                 enter_loading_screen_endless
             ),
+            widget::button("Watch Last Run", fonts.default.clone(), watch_last_replay),
+            widget::button("Leaderboard", fonts.default.clone(), open_leaderboard_menu),
             widget::button("Settings", fonts.default.clone(), open_settings_menu),
             widget::button("Credits", fonts.default.clone(), open_credits_menu),
         ],
@@ -71,6 +79,10 @@ fn open_settings_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextStat
     next_menu.set(Menu::Settings);
 }
 
+fn open_leaderboard_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Leaderboard);
+}
+
 fn open_credits_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
     next_menu.set(Menu::Credits);
 }
@@ -4,6 +4,7 @@ mod assets;
 mod credits;
 pub(crate) mod game_over;
 pub(crate) mod game_won;
+pub(crate) mod leaderboard;
 mod main;
 mod pause;
 mod settings;
@@ -17,6 +18,7 @@ pub(super) fn plugin(app: &mut App) {
     app.add_plugins((
         assets::plugin,
         credits::plugin,
+        leaderboard::plugin,
         main::plugin,
         settings::plugin,
         pause::plugin,
@@ -34,6 +36,7 @@ pub(crate) enum Menu {
     None,
     Main,
     Credits,
+    Leaderboard,
     Settings,
     Pause,
 }
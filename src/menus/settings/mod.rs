@@ -2,6 +2,8 @@
 //! We can add all manner of settings and accessibility options here.
 //! For 3D, we'd also place the camera sensitivity and FOV here.
 
+mod persistence;
+
 use std::time::Duration;
 
 use bevy::{
@@ -24,6 +26,7 @@ use crate::{
 };
 
 pub(super) fn plugin(app: &mut App) {
+    app.add_plugins(persistence::plugin);
     app.init_resource::<VolumeSliderSettings>();
     app.add_systems(OnEnter(Menu::Settings), spawn_settings_menu);
     app.add_systems(
@@ -307,13 +310,15 @@ fn update_volume_label(
 #[reflect(Component)]
 struct CameraSensitivityLabel;
 
+const MIN_SENSITIVITY: f32 = 0.1;
+const MAX_SENSITIVITY: f32 = 20.0;
+
 #[cfg_attr(feature = "hot_patch", hot)]
 fn lower_camera_sensitivity(
     _trigger: Trigger<Pointer<Click>>,
     mut camera_sensitivity: ResMut<CameraSensitivity>,
 ) {
     camera_sensitivity.0 -= 0.1;
-    const MIN_SENSITIVITY: f32 = 0.1;
     camera_sensitivity.x = camera_sensitivity.x.max(MIN_SENSITIVITY);
     camera_sensitivity.y = camera_sensitivity.y.max(MIN_SENSITIVITY);
 }
@@ -324,7 +329,6 @@ fn raise_camera_sensitivity(
     mut camera_sensitivity: ResMut<CameraSensitivity>,
 ) {
     camera_sensitivity.0 += 0.1;
-    const MAX_SENSITIVITY: f32 = 20.0;
     camera_sensitivity.x = camera_sensitivity.x.min(MAX_SENSITIVITY);
     camera_sensitivity.y = camera_sensitivity.y.min(MAX_SENSITIVITY);
 }
@@ -341,15 +345,18 @@ fn update_camera_sensitivity_label(
 #[reflect(Component)]
 struct CameraFovLabel;
 
+const MIN_FOV: f32 = 45.0;
+const MAX_FOV: f32 = 130.0;
+
 fn lower_camera_fov(_trigger: Trigger<Pointer<Click>>, mut camera_fov: ResMut<WorldModelFov>) {
     camera_fov.0 -= 1.0;
-    camera_fov.0 = camera_fov.0.max(45.0);
+    camera_fov.0 = camera_fov.0.max(MIN_FOV);
 }
 
 #[cfg_attr(feature = "hot_patch", hot)]
 fn raise_camera_fov(_trigger: Trigger<Pointer<Click>>, mut camera_fov: ResMut<WorldModelFov>) {
     camera_fov.0 += 1.0;
-    camera_fov.0 = camera_fov.0.min(130.0);
+    camera_fov.0 = camera_fov.0.min(MAX_FOV);
 }
 
 #[cfg_attr(feature = "hot_patch", hot)]
@@ -0,0 +1,151 @@
+//! Saving the settings when they change and loading them on startup.
+//!
+//! The settings are stored as a versioned RON file, see [`crate::persistence`] for where.
+//! Every field has a default, so files written by older versions that lack newer fields
+//! still load, and fields that changed meaning are converted in [`SettingsFile::migrate`].
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{MAX_FOV, MAX_SENSITIVITY, MIN_FOV, MIN_SENSITIVITY, VolumeSliderSettings};
+use crate::{
+    gameplay::{
+        gore_settings::GoreSettings,
+        player::camera::{CameraSensitivity, MouseInversion, WorldModelFov},
+    },
+    persistence,
+};
+
+/// The key the settings are stored under.
+const STORAGE_KEY: &str = "settings.ron";
+
+/// The version of [`SettingsFile`] written by this build.
+/// Bump this and add a step to [`SettingsFile::migrate`] when the meaning of a field changes.
+/// Adding a field with a default does not require a new version.
+const SETTINGS_VERSION: u32 = 1;
+
+pub(super) fn plugin(app: &mut App) {
+    // Insert the settings right away, so that they are in place before any menu is spawned.
+    if let Some(settings) = SettingsFile::load() {
+        settings.insert_into(app.world_mut());
+    }
+
+    app.add_systems(
+        Update,
+        save_settings.run_if(
+            resource_changed::<VolumeSliderSettings>
+                .or(resource_changed::<CameraSensitivity>)
+                .or(resource_changed::<WorldModelFov>)
+                .or(resource_changed::<MouseInversion>)
+                .or(resource_changed::<GoreSettings>),
+        ),
+    );
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+struct SettingsFile {
+    /// The version the file was written by. Files written before versioning have version 0.
+    version: u32,
+    volume: usize,
+    camera_sensitivity: [f32; 2],
+    invert_mouse_y: bool,
+    fov: f32,
+    gore: GoreSettings,
+}
+
+impl Default for SettingsFile {
+    fn default() -> Self {
+        Self {
+            version: 0,
+            volume: VolumeSliderSettings::default().0,
+            camera_sensitivity: CameraSensitivity::default().to_array(),
+            invert_mouse_y: MouseInversion::default().invert_mouse_y,
+            fov: WorldModelFov::default().0,
+            gore: GoreSettings::default(),
+        }
+    }
+}
+
+impl SettingsFile {
+    fn load() -> Option<Self> {
+        let text = match persistence::read(STORAGE_KEY) {
+            Ok(text) => text?,
+            Err(err) => {
+                error!("Failed to load the settings, using the defaults: {err:#}");
+                return None;
+            }
+        };
+        match ron::from_str::<Self>(&text) {
+            Ok(settings) => Some(settings.migrate()),
+            Err(err) => {
+                error!("Failed to parse the settings, using the defaults: {err}");
+                None
+            }
+        }
+    }
+
+    /// Converts settings written by an older version to the current version.
+    fn migrate(mut self) -> Self {
+        if self.version > SETTINGS_VERSION {
+            warn!(
+                "The settings were written by a newer version ({}), ignoring unknown fields",
+                self.version
+            );
+        }
+        // Version 0 files only differ in lacking the version, which is filled in below.
+        // Steps for future versions go here, e.g. `if self.version < 2 { ... }`.
+        self.version = SETTINGS_VERSION;
+        self
+    }
+
+    fn insert_into(self, world: &mut World) {
+        let volume_slider =
+            VolumeSliderSettings(self.volume.min(VolumeSliderSettings::MAX_TICK_COUNT));
+        world.insert_resource(GlobalVolume::new(volume_slider.volume()));
+        world.insert_resource(volume_slider);
+        let [x, y] = self.camera_sensitivity;
+        let default_sensitivity = CameraSensitivity::default();
+        world.insert_resource(CameraSensitivity(Vec2::new(
+            sanitize(x, MIN_SENSITIVITY, MAX_SENSITIVITY, default_sensitivity.x),
+            sanitize(y, MIN_SENSITIVITY, MAX_SENSITIVITY, default_sensitivity.y),
+        )));
+        world.insert_resource(MouseInversion {
+            invert_mouse_y: self.invert_mouse_y,
+        });
+        let fov = sanitize(self.fov, MIN_FOV, MAX_FOV, WorldModelFov::default().0);
+        world.insert_resource(WorldModelFov(fov));
+        world.insert_resource(self.gore);
+    }
+
+    fn from_world(world: &World) -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            volume: world.resource::<VolumeSliderSettings>().0,
+            camera_sensitivity: world.resource::<CameraSensitivity>().to_array(),
+            invert_mouse_y: world.resource::<MouseInversion>().invert_mouse_y,
+            fov: world.resource::<WorldModelFov>().0,
+            gore: world.resource::<GoreSettings>().clone(),
+        }
+    }
+}
+
+/// Clamps a setting read from the file to the range the settings menu allows.
+/// Values that are not finite, which the menu never produces, are replaced by the default.
+fn sanitize(value: f32, min: f32, max: f32, default: f32) -> f32 {
+    if value.is_finite() {
+        value.clamp(min, max)
+    } else {
+        default
+    }
+}
+
+fn save_settings(world: &mut World) {
+    let settings = SettingsFile::from_world(world);
+    let result = ron::ser::to_string_pretty(&settings, default())
+        .map_err(anyhow::Error::from)
+        .and_then(|text| persistence::write(STORAGE_KEY, &text));
+    if let Err(err) = result {
+        error!("Failed to save the settings: {err:#}");
+    }
+}
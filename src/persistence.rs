@@ -0,0 +1,95 @@
+//! Storage for data that should survive restarts, such as the leaderboard.
+//!
+//! Native builds store each key as a file in the platform's data directory,
+//! e.g. `~/.local/share/chainboom` on Linux. Web builds use the browser's `localStorage`.
+
+#[cfg(not(target_family = "wasm"))]
+use anyhow::Context as _;
+
+/// Reads the data stored under `key`, or `None` if nothing was stored yet.
+#[cfg(not(target_family = "wasm"))]
+pub(crate) fn read(key: &str) -> anyhow::Result<Option<String>> {
+    let path = native::data_dir().join(key);
+    match std::fs::read_to_string(&path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+/// Stores `contents` under `key`, replacing what was stored before.
+#[cfg(not(target_family = "wasm"))]
+pub(crate) fn write(key: &str, contents: &str) -> anyhow::Result<()> {
+    write_bytes(key, contents.as_bytes()).map(|_| ())
+}
+
+/// Stores binary `contents` under `key` and returns the path of the file.
+/// Keys may contain `/` to group files into folders.
+/// Only available on native builds, as the browser's storage only holds strings.
+#[cfg(not(target_family = "wasm"))]
+pub(crate) fn write_bytes(key: &str, contents: &[u8]) -> anyhow::Result<std::path::PathBuf> {
+    let path = native::data_dir().join(key);
+    if let Some(directory) = path.parent() {
+        std::fs::create_dir_all(directory)
+            .with_context(|| format!("Failed to create {}", directory.display()))?;
+    }
+    std::fs::write(&path, contents)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+#[cfg(not(target_family = "wasm"))]
+mod native {
+    use std::{env, path::PathBuf};
+
+    /// The directory our data is stored in. Falls back to the working directory
+    /// if the platform's data directory cannot be determined.
+    pub(super) fn data_dir() -> PathBuf {
+        let home = || env::var_os("HOME").map(PathBuf::from);
+        let base = if cfg!(target_os = "windows") {
+            env::var_os("APPDATA").map(PathBuf::from)
+        } else if cfg!(target_os = "macos") {
+            home().map(|home| home.join("Library").join("Application Support"))
+        } else {
+            env::var_os("XDG_DATA_HOME")
+                .map(PathBuf::from)
+                .or_else(|| home().map(|home| home.join(".local").join("share")))
+        };
+        base.unwrap_or_default().join("chainboom")
+    }
+}
+
+/// Reads the data stored under `key`, or `None` if nothing was stored yet.
+#[cfg(target_family = "wasm")]
+pub(crate) fn read(key: &str) -> anyhow::Result<Option<String>> {
+    web::local_storage()?
+        .get_item(&web::storage_key(key))
+        .map_err(|err| anyhow::anyhow!("Failed to read {key} from local storage: {err:?}"))
+}
+
+/// Stores `contents` under `key`, replacing what was stored before.
+#[cfg(target_family = "wasm")]
+pub(crate) fn write(key: &str, contents: &str) -> anyhow::Result<()> {
+    web::local_storage()?
+        .set_item(&web::storage_key(key), contents)
+        .map_err(|err| anyhow::anyhow!("Failed to write {key} to local storage: {err:?}"))
+}
+
+#[cfg(target_family = "wasm")]
+mod web {
+    use anyhow::Context as _;
+
+    pub(super) fn local_storage() -> anyhow::Result<web_sys::Storage> {
+        web_sys::window()
+            .context("There is no browser window")?
+            .local_storage()
+            .ok()
+            .flatten()
+            .context("Local storage is not available")
+    }
+
+    /// Prefixes keys, as other games on the same origin share the local storage.
+    pub(super) fn storage_key(key: &str) -> String {
+        format!("chainboom/{key}")
+    }
+}
@@ -1,5 +1,5 @@
 use crate::gameplay::{
-    explosion::{ExplodeOnShoot, effects::PropExplosionVfx},
+    explosion::{ExplodeOnShoot, ExplosionFalloff, Explosive, effects::PropExplosionVfx},
     health::Health,
 };
 
@@ -21,6 +21,8 @@ pub(super) fn plugin(app: &mut App) {
 
     app.add_observer(setup_nonphysical_prop::<IvyPart8>);
 
+    app.add_observer(setup_barrel_explosive);
+
     app.register_type::<Table>();
     app.register_type::<Bookshelf>();
     app.register_type::<Generator2>();
@@ -78,7 +80,45 @@ pub(crate) struct Generator2;
 #[model("models/darkmod/containers/barrel_large_closed.gltf")]
 #[spawn_hooks(SpawnHooks::new().preload_model::<Self>())]
 #[require(ExplodeOnShoot, PropExplosionVfx, Health = Health::new(10.0))]
-pub(crate) struct BarrelLargeClosed;
+pub(crate) struct BarrelLargeClosed {
+    /// The radius beyond which the explosion has no effect.
+    pub(crate) explosion_radius: f32,
+    /// The radius within which the explosion deals full damage and impulse.
+    pub(crate) explosion_inner_radius: f32,
+    /// How damage and impulse decrease between the two radii: `none`, `linear`, `inverse_square`,
+    /// or evenly spaced samples of a custom curve, e.g. `1, 0.9, 0.5, 0`.
+    pub(crate) explosion_falloff: String,
+}
+
+impl Default for BarrelLargeClosed {
+    fn default() -> Self {
+        let explosive = Explosive::default();
+        Self {
+            explosion_radius: explosive.radius,
+            explosion_inner_radius: explosive.inner_radius,
+            explosion_falloff: "linear".to_string(),
+        }
+    }
+}
+
+fn setup_barrel_explosive(
+    trigger: Trigger<OnAdd, BarrelLargeClosed>,
+    mut barrels: Query<(&BarrelLargeClosed, &mut Explosive)>,
+) {
+    let entity = trigger.target();
+    let Ok((barrel, mut explosive)) = barrels.get_mut(entity) else {
+        error!("Barrel entity {entity} has no explosive");
+        return;
+    };
+    explosive.radius = barrel.explosion_radius;
+    explosive.inner_radius = barrel.explosion_inner_radius;
+    match barrel.explosion_falloff.parse::<ExplosionFalloff>() {
+        Ok(falloff) => explosive.falloff = falloff,
+        Err(err) => {
+            error!("Barrel {entity} has invalid `explosion_falloff`, using the default: {err:#}")
+        }
+    }
+}
 
 #[derive(PointClass, Component, Debug, Reflect)]
 #[reflect(QuakeClass, Component)]
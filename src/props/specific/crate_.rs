@@ -6,6 +6,7 @@ use bevy_simple_subsecond_system::hot;
 use bevy_trenchbroom::prelude::*;
 
 use crate::{
+    gameplay::explosion::BlastShield,
     props::setup::setup_dynamic_prop_with_convex_hull,
     third_party::{avian3d::CollisionLayer, bevy_trenchbroom::LoadTrenchbroomModel as _},
 };
@@ -13,6 +14,7 @@ use crate::{
 pub(super) fn plugin(app: &mut App) {
     app.add_observer(setup_crate_small);
     app.add_observer(setup_dynamic_prop_with_convex_hull::<CrateBig>);
+    app.add_observer(setup_crate_big_blast_shield);
     app.register_type::<CrateBig>();
     app.register_type::<CrateSmall>();
 }
@@ -22,7 +24,19 @@ pub(super) fn plugin(app: &mut App) {
 #[base(Transform, Visibility)]
 #[model("models/darkmod/containers/crate01_big.gltf")]
 #[spawn_hooks(SpawnHooks::new().preload_model::<Self>())]
-pub(crate) struct CrateBig;
+pub(crate) struct CrateBig {
+    /// How much of an explosion this crate absorbs, from 0 (none) to 1 (all).
+    /// Set to 0 to let explosions pass through it.
+    blast_absorption: f32,
+}
+
+impl Default for CrateBig {
+    fn default() -> Self {
+        Self {
+            blast_absorption: BlastShield::default().absorption,
+        }
+    }
+}
 
 #[derive(PointClass, Component, Debug, Reflect)]
 #[reflect(QuakeClass, Component)]
@@ -31,6 +45,21 @@ pub(crate) struct CrateBig;
 #[spawn_hooks(SpawnHooks::new().preload_model::<Self>())]
 pub(crate) struct CrateSmall;
 
+fn setup_crate_big_blast_shield(
+    trigger: Trigger<OnAdd, CrateBig>,
+    crates: Query<&CrateBig>,
+    mut commands: Commands,
+) {
+    let Ok(crate_big) = crates.get(trigger.target()) else {
+        return;
+    };
+    if crate_big.blast_absorption > 0.0 {
+        commands.entity(trigger.target()).insert(BlastShield {
+            absorption: crate_big.blast_absorption,
+        });
+    }
+}
+
 #[cfg_attr(feature = "hot_patch", hot)]
 fn setup_crate_small(
     trigger: Trigger<OnAdd, CrateSmall>,
@@ -6,6 +6,7 @@ use bevy::{
 
 use crate::{
     Pause,
+    gameplay::replay::Replay,
     menus::{Menu, game_over::GameOverMenu, game_won::GameWonMenu},
     screens::Screen,
 };
@@ -20,7 +21,9 @@ pub(super) fn plugin(app: &mut App) {
                     .and(in_state(Menu::None))
                     .and(input_just_pressed(KeyCode::KeyP).or(input_just_pressed(KeyCode::Escape)))
                     .and(not(any_with_component::<GameOverMenu>))
-                    .and(not(any_with_component::<GameWonMenu>)),
+                    .and(not(any_with_component::<GameWonMenu>))
+                    // Replays are quit with Escape instead.
+                    .and(not(resource_exists::<Replay>)),
             ),
             close_menu.run_if(
                 in_state(Screen::Gameplay)
@@ -7,7 +7,7 @@ use bevy_simple_subsecond_system::hot;
 
 use crate::{
     font::FontAssets,
-    gameplay::level::spawn_level,
+    gameplay::{level::spawn_level, waves::Waves},
     screens::Screen,
     theme::{palette::SCREEN_BACKGROUND, prelude::*},
 };
@@ -43,6 +43,7 @@ fn advance_to_gameplay_screen(
     just_added_scenes: Query<(), (With<SceneRoot>, Without<SceneInstance>)>,
     just_added_meshes: Query<(), Added<Mesh3d>>,
     nav_mesh_events: EventReader<AssetEvent<NavMesh<ThreeD>>>,
+    waves: Query<(), With<Waves>>,
 ) {
     if !(just_added_meshes.is_empty() && just_added_scenes.is_empty()) {
         return;
@@ -50,6 +51,10 @@ fn advance_to_gameplay_screen(
     if !nav_mesh_events.is_empty() {
         return;
     }
+    // The level's wave table may still be loading.
+    if waves.is_empty() {
+        return;
+    }
 
     for scene_instance in scene_instances.iter() {
         if !scene_spawner.instance_is_ready(**scene_instance) {
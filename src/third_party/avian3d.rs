@@ -20,6 +20,8 @@ pub(crate) enum CollisionLayer {
     Sensor,
     Npc,
     Gib,
+    /// The colliders on the skeletons of NPCs that shots hit.
+    HitZone,
 }
 
 #[cfg_attr(feature = "hot_patch", hot)]
@@ -35,22 +35,31 @@ pub(super) fn plugin(app: &mut App) {
     app.add_observer(add_nav_mesh_affector_to_colliders_under_nav_mesh_affector_parent);
 }
 
+/// The type of the navmesh around armed explosives, see [`DangerZoneOf`](crate::gameplay::npc::danger::DangerZoneOf).
+/// Oxidized Navigation calls this an area, which becomes the type index of the landmass polygons.
+pub(crate) const DANGER_TYPE_INDEX: usize = 1;
+
 #[cfg_attr(feature = "hot_patch", hot)]
 fn setup_archipelago(mut commands: Commands) {
+    let mut archipelago = Archipelago3d::new(AgentOptions {
+        point_sample_distance: PointSampleDistance3d {
+            horizontal_distance: 0.6,
+            distance_above: 1.0,
+            distance_below: 1.0,
+            vertical_preference_ratio: 2.0,
+        },
+        ..AgentOptions::from_agent_radius(NPC_RADIUS)
+    });
+    // Danger only costs extra for agents that override it.
+    archipelago
+        .set_type_index_cost(DANGER_TYPE_INDEX, 1.0)
+        .expect("The cost of a type index must be positive");
     // This *should* be scoped to the `Screen::Gameplay` state, but doing so
     // seems to never regenerate the nav mesh when the level is loaded the second
     // time.
     commands.spawn((
         Name::new("Main Level Archipelago"),
-        Archipelago3d::new(AgentOptions {
-            point_sample_distance: PointSampleDistance3d {
-                horizontal_distance: 0.6,
-                distance_above: 1.0,
-                distance_below: 1.0,
-                vertical_preference_ratio: 2.0,
-            },
-            ..AgentOptions::from_agent_radius(NPC_RADIUS)
-        }),
+        archipelago,
         OxidizedArchipelago,
     ));
 }